@@ -0,0 +1,67 @@
+use std::{env, fs, process};
+
+use bandersnatch_wasm::{Engine, epd::{EpdReport, SearchLimit}};
+
+const USAGE: &str = "usage: bandersnatch-epd <file.epd> [--depth N | --time MS]";
+
+fn parse_args() -> Option<(String, SearchLimit)> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let mut path = None;
+    let mut limit = SearchLimit::Time(1000);
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--depth" => limit = SearchLimit::Depth(iter.next()?.parse().ok()?),
+            "--time" => limit = SearchLimit::Time(iter.next()?.parse().ok()?),
+            _ => path = Some(arg.clone())
+        }
+    }
+
+    Some((path?, limit))
+}
+
+// searches every position of an epd suite and prints which ones were solved
+fn main() {
+    let (path, limit) = match parse_args() {
+        Some(args) => args,
+        None => {
+            eprintln!("{}", USAGE);
+            process::exit(2);
+        }
+    };
+    let text = match fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(e) => {
+            eprintln!("cannot read {}: {}", path, e);
+            process::exit(1);
+        }
+    };
+
+    let mut engine = Engine::new();
+    let mut report = EpdReport::default();
+    for (number, line) in text.lines().enumerate().filter(|(_, l)| !l.trim().is_empty()) {
+        let position = match engine.parse_epd(line) {
+            Ok(position) => position,
+            Err(e) => {
+                eprintln!("line {}: {}", number + 1, e);
+                continue;
+            }
+        };
+
+        // one position at a time so progress is printed as the suite runs
+        let result = engine.run_epd(&[position], limit).results.remove(0);
+        println!(
+            "{:<24} {:<8} {:<8} {:<8} depth {:<3} {} ms",
+            result.id.as_deref().unwrap_or("-"),
+            result.san,
+            if result.solved { "solved" } else { "failed" },
+            result.mate.map(|m| format!("mate {}", m)).unwrap_or_default(),
+            result.depth,
+            result.time
+        );
+        report.results.push(result);
+    }
+
+    println!("solved {}/{} in {} ms", report.solved(), report.results.len(), report.total_time());
+}
@@ -0,0 +1,21 @@
+mod search;
+mod uci;
+mod xboard;
+
+use std::{io::{self, BufRead}, iter};
+
+// speaks uci unless the first command asks for xboard
+fn main() {
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines().map_while(Result::ok);
+    let first = match lines.next() {
+        Some(line) => line,
+        None => return
+    };
+
+    if first.trim() == "xboard" {
+        xboard::run(lines);
+    } else {
+        uci::run(iter::once(first).chain(lines));
+    }
+}
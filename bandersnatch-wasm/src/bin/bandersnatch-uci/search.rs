@@ -0,0 +1,81 @@
+use std::{sync::{Arc, atomic::{AtomicBool, Ordering}}, thread::{self, JoinHandle}};
+
+use bandersnatch_wasm::{Engine, defs::Move};
+
+// coordinate notation shared by uci and xboard (e2e4, e7e8q)
+pub fn find_move(engine: &Engine, mov: &str) -> Option<Move> {
+    let mov = mov.to_ascii_lowercase();
+    engine.valid_moves()
+        .iter()
+        .find(|m| m.to_uci() == mov)
+        .copied()
+}
+
+// fall back to any legal move when the search was stopped before finishing its first iteration
+pub fn search_result(engine: &Engine) -> Option<Move> {
+    let best_move = engine.last_best_move().mov;
+    if engine.depth_searched_last_turn() > 0 && engine.valid_moves().contains(&best_move) {
+        return Some(best_move);
+    }
+    engine.valid_moves().first().copied()
+}
+
+// owns the engine and lends it to a background thread while searching
+pub struct SearchThread {
+    engine: Option<Engine>,
+    handle: Option<JoinHandle<Engine>>,
+    stop_signal: Arc<AtomicBool>,
+}
+
+impl SearchThread {
+    pub fn new(engine: Engine) -> SearchThread {
+        SearchThread {
+            stop_signal: engine.stop_signal(),
+            engine: Some(engine),
+            handle: None,
+        }
+    }
+
+    // stops any running search before handing out the engine
+    pub fn engine(&mut self) -> &mut Engine {
+        self.stop();
+        self.engine.as_mut().unwrap()
+    }
+
+    pub fn replace_engine(&mut self, engine: Engine) {
+        self.stop();
+        self.stop_signal = engine.stop_signal();
+        self.engine = Some(engine);
+    }
+
+    pub fn start<F>(&mut self, search: F)
+    where F: FnOnce(&mut Engine, &AtomicBool) + Send + 'static {
+        self.stop();
+        let mut engine = self.engine.take().unwrap();
+        let stop_signal = self.stop_signal.clone();
+        stop_signal.store(false, Ordering::Relaxed);
+        self.handle = Some(thread::spawn(move || {
+            search(&mut engine, &stop_signal);
+            engine
+        }));
+    }
+
+    // true until a started search has returned, it may not have been joined yet
+    pub fn is_searching(&self) -> bool {
+        self.handle.as_ref().is_some_and(|h| !h.is_finished())
+    }
+
+    // lets a running search finish on its own
+    pub fn wait(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            self.engine = Some(handle.join().unwrap());
+        }
+    }
+
+    pub fn stop(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            self.stop_signal.store(true, Ordering::Relaxed);
+            self.engine = Some(handle.join().unwrap());
+        }
+    }
+}
@@ -0,0 +1,271 @@
+use std::{sync::atomic::{AtomicBool, Ordering}, thread, time::{Duration, Instant}};
+
+use bandersnatch_wasm::{Engine, defs::mate_in, fen::STARTPOS_FEN, platform::{InfoSink, SearchInfo}, time_control::{SearchBudget, allocate}, transposition::DEFAULT_HASH_SIZE};
+
+use crate::search::{SearchThread, find_move, search_result};
+
+struct UciInfoSink;
+impl InfoSink for UciInfoSink {
+    fn post_info(&mut self, info: &SearchInfo) {
+        println!(
+            "info depth {} multipv {} score {} nodes {} time {} pv {}",
+            info.depth,
+            info.multipv,
+            format_score(info.score),
+            info.nodes,
+            info.time,
+            info.pv.iter().map(|m| m.to_uci()).collect::<Vec<String>>().join(" ")
+        );
+    }
+}
+
+fn format_score(score: i32) -> String {
+    match mate_in(score) {
+        Some(moves) => format!("mate {}", moves),
+        None => format!("cp {}", score)
+    }
+}
+
+fn new_engine(hash_size: usize, multi_pv: usize) -> Engine {
+    let mut engine = Engine::new();
+    engine.set_hash_size(hash_size);
+    engine.set_multi_pv(multi_pv);
+    engine.set_info_sink(Box::new(UciInfoSink));
+    engine.parse_fen(STARTPOS_FEN).unwrap();
+    engine
+}
+
+const MAX_HASH_SIZE: usize = 1024; // MB
+const MAX_MULTI_PV: usize = 8;
+
+#[derive(Default)]
+struct GoParams {
+    wtime: Option<u32>,
+    btime: Option<u32>,
+    winc: u32,
+    binc: u32,
+    moves_to_go: Option<u32>,
+    depth: Option<i32>,
+    move_time: Option<u32>,
+    infinite: bool,
+}
+
+impl GoParams {
+    fn parse(args: &[&str]) -> GoParams {
+        let mut params: GoParams = Default::default();
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            match *arg {
+                "infinite" => params.infinite = true,
+                "wtime" | "btime" | "winc" | "binc" | "movestogo" | "depth" | "movetime" => {
+                    // negative clock values are sent by some guis when flagging
+                    let value: i64 = match iter.next().and_then(|v| v.parse().ok()) {
+                        Some(v) => v,
+                        None => continue
+                    };
+                    let time = value.max(0) as u32;
+                    match *arg {
+                        "wtime" => params.wtime = Some(time),
+                        "btime" => params.btime = Some(time),
+                        "winc" => params.winc = time,
+                        "binc" => params.binc = time,
+                        "movestogo" => params.moves_to_go = Some(time),
+                        "depth" => params.depth = Some(value as i32),
+                        _ => params.move_time = Some(time),
+                    }
+                },
+                _ => {}
+            }
+        }
+        params
+    }
+
+    // time to spend on this move
+    fn search_budget(&self, white_turn: bool) -> SearchBudget {
+        if self.infinite {
+            return SearchBudget::fixed(u32::MAX);
+        }
+        if let Some(move_time) = self.move_time {
+            return SearchBudget::fixed(move_time);
+        }
+
+        let (remaining, increment) = if white_turn { (self.wtime, self.winc) } else { (self.btime, self.binc) };
+        match remaining {
+            Some(remaining) => allocate(remaining, increment, self.moves_to_go),
+            None if self.depth.is_some() => SearchBudget::fixed(u32::MAX),
+            None => SearchBudget::fixed(3000)
+        }
+    }
+}
+
+fn set_position(engine: &mut Engine, args: &[&str]) {
+    let moves_index = args.iter().position(|a| *a == "moves").unwrap_or(args.len());
+    let result = match args.first() {
+        Some(&"startpos") => engine.parse_fen(STARTPOS_FEN),
+        Some(&"fen") => engine.parse_fen(&args[1..moves_index].join(" ")),
+        _ => return
+    };
+    if let Err(e) = result {
+        println!("info string invalid fen: {}", e);
+        return;
+    }
+
+    for mov in args.iter().skip(moves_index + 1) {
+        match find_move(engine, mov) {
+            Some(m) => engine.play_move(m),
+            None => {
+                println!("info string illegal move {}", mov);
+                return;
+            }
+        }
+    }
+}
+
+// go perft N prints the node count below each root move, then the total
+fn perft(engine: &mut Engine, args: &[&str]) {
+    let depth: i32 = match args.first().and_then(|d| d.parse().ok()) {
+        Some(d) => d,
+        None => return
+    };
+
+    let start = Instant::now();
+    let divide = engine.perft_divide(depth, true);
+    let total: u64 = divide.iter().map(|e| e.nodes).sum();
+    for entry in divide.iter() {
+        println!("{}: {}", entry.mov.to_uci(), entry.nodes);
+    }
+    println!();
+    println!("Nodes searched: {}", total);
+    println!("info string perft time {} ms", start.elapsed().as_millis());
+}
+
+fn go(search: &mut SearchThread, args: &[&str]) {
+    if args.first() == Some(&"perft") {
+        perft(search.engine(), &args[1..]);
+        return;
+    }
+
+    let params = GoParams::parse(args);
+    let engine = search.engine();
+    engine.set_search_budget(params.search_budget(engine.white_turn()));
+    engine.update_max_search_depth(params.depth.unwrap_or(30));
+
+    let infinite = params.infinite;
+    search.start(move |engine: &mut Engine, stop_signal: &AtomicBool| {
+        engine.find_best_move_iterative();
+
+        // bestmove cannot be sent until the gui stops an infinite search
+        while infinite && !stop_signal.load(Ordering::Relaxed) {
+            thread::sleep(Duration::from_millis(5));
+        }
+
+        match search_result(engine) {
+            Some(m) => println!("bestmove {}", m.to_uci()),
+            None => println!("bestmove 0000")
+        }
+    });
+}
+
+pub fn run<I: Iterator<Item = String>>(lines: I) {
+    let mut hash_size = DEFAULT_HASH_SIZE;
+    let mut multi_pv = 1;
+    let mut search = SearchThread::new(new_engine(hash_size, multi_pv));
+
+    for line in lines {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        let (command, args) = match tokens.split_first() {
+            Some((command, args)) => (*command, args),
+            None => continue
+        };
+
+        match command {
+            "uci" => {
+                println!("id name Bandersnatch {}", env!("CARGO_PKG_VERSION"));
+                println!("id author TheApplePieGod");
+                println!("option name Hash type spin default {} min 1 max {}", DEFAULT_HASH_SIZE, MAX_HASH_SIZE);
+                println!("option name MultiPV type spin default 1 min 1 max {}", MAX_MULTI_PV);
+                println!("uciok");
+            },
+            "isready" => println!("readyok"),
+            "setoption" => {
+                // setoption name Hash value N
+                if let ["name", name, "value", value] = args {
+                    let value = match value.parse::<usize>() {
+                        Ok(v) => v,
+                        Err(_) => continue
+                    };
+                    if name.eq_ignore_ascii_case("hash") {
+                        hash_size = value.clamp(1, MAX_HASH_SIZE);
+                        search.engine().set_hash_size(hash_size);
+                    } else if name.eq_ignore_ascii_case("multipv") {
+                        multi_pv = value.clamp(1, MAX_MULTI_PV);
+                        search.engine().set_multi_pv(multi_pv);
+                    }
+                }
+            },
+            "ucinewgame" => search.replace_engine(new_engine(hash_size, multi_pv)),
+            "position" => set_position(search.engine(), args),
+            "go" => go(&mut search, args),
+            "stop" => search.stop(),
+            "quit" => break,
+            _ => {}
+        }
+    }
+
+    search.stop();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn go_params(command: &str) -> GoParams {
+        GoParams::parse(&command.split_whitespace().collect::<Vec<&str>>())
+    }
+
+    fn position(command: &str) -> Engine {
+        let mut engine = Engine::new();
+        set_position(&mut engine, &command.split_whitespace().collect::<Vec<&str>>());
+        engine
+    }
+
+    #[test]
+    fn clocks_are_read_for_the_side_to_move() {
+        let params = go_params("wtime 60000 btime 30000 winc 1000 binc 500 movestogo 20");
+        assert_eq!((params.wtime, params.btime, params.winc, params.binc, params.moves_to_go), (Some(60000), Some(30000), 1000, 500, Some(20)));
+        assert_eq!(params.search_budget(true), allocate(60000, 1000, Some(20)));
+        assert_eq!(params.search_budget(false), allocate(30000, 500, Some(20)));
+
+        // a flagged clock is sent as a negative time
+        let params = go_params("wtime -15 btime 1000");
+        assert_eq!(params.wtime, Some(0));
+    }
+
+    #[test]
+    fn fixed_searches_ignore_the_clocks() {
+        assert_eq!(go_params("wtime 60000 movetime 250").search_budget(true), SearchBudget::fixed(250));
+        assert_eq!(go_params("wtime 60000 infinite").search_budget(true), SearchBudget::fixed(u32::MAX));
+
+        let params = go_params("depth 7");
+        assert_eq!(params.depth, Some(7));
+        assert_eq!(params.search_budget(true), SearchBudget::fixed(u32::MAX));
+
+        // without any limit the search gets a few seconds
+        assert_eq!(go_params("").search_budget(false), SearchBudget::fixed(3000));
+        // a missing value is skipped rather than read from the next keyword
+        assert_eq!(go_params("movetime depth 3").move_time, None);
+    }
+
+    #[test]
+    fn positions_are_set_up_with_their_moves() {
+        let engine = position("startpos moves e2e4 e7e5 g1f3");
+        assert_eq!(engine.to_fen(), "rnbqkbnr/pppp1ppp/8/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2");
+
+        let engine = position("fen 4k3/8/8/8/8/8/4P3/4K3 w - - 0 30 moves e2e4 e8d7");
+        assert_eq!(engine.to_fen(), "8/3k4/8/8/4P3/8/8/4K3 w - - 1 31");
+
+        // play stops at the first illegal move
+        let engine = position("startpos moves e2e4 e2e4 e7e5");
+        assert_eq!(engine.to_fen(), "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1");
+    }
+}
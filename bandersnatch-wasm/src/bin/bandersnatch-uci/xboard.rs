@@ -0,0 +1,358 @@
+use std::sync::{Arc, Mutex, atomic::{AtomicBool, Ordering}};
+
+use bandersnatch_wasm::{Engine, defs::{Move, mate_in}, fen::STARTPOS_FEN, platform::{InfoSink, SearchInfo}, status::GameStatus, time_control::{SearchBudget, allocate}};
+
+use crate::search::{SearchThread, find_move, search_result};
+
+struct XBoardInfoSink {
+    post: Arc<AtomicBool>
+}
+
+impl InfoSink for XBoardInfoSink {
+    fn post_info(&mut self, info: &SearchInfo) {
+        if !self.post.load(Ordering::Relaxed) {
+            return;
+        }
+
+        // ply score time(cs) nodes pv
+        println!(
+            "{} {} {} {} {}",
+            info.depth,
+            format_score(info.score),
+            info.time / 10,
+            info.nodes,
+            info.pv_san
+        );
+    }
+}
+
+// xboard reports mates as 100000 + moves
+fn format_score(score: i32) -> i32 {
+    match mate_in(score) {
+        Some(moves) if moves > 0 => 100000 + moves,
+        Some(moves) => -100000 + moves,
+        None => score
+    }
+}
+
+fn game_result(engine: &Engine) -> Option<String> {
+    let status = engine.game_status();
+    if status == GameStatus::Ongoing {
+        return None;
+    }
+
+    Some(format!("{} {{{}}}", status.result(), status))
+}
+
+// moves are replayed from the starting position to support undo
+struct Game {
+    start_fen: String,
+    start_ply: u32, // from the fen's fullmove number and side to move
+    moves: Vec<Move>,
+}
+
+impl Game {
+    fn new(fen: &str) -> Game {
+        let fields: Vec<&str> = fen.split_whitespace().collect();
+        let full_moves = fields.get(5).and_then(|f| f.parse::<u32>().ok()).unwrap_or(1).max(1);
+        Game {
+            start_fen: String::from(fen),
+            start_ply: (full_moves - 1) * 2 + (fields.get(1) == Some(&"b")) as u32,
+            moves: vec![],
+        }
+    }
+
+    // moves the side to move has made so far, which is what time control periods count
+    fn moves_played(&self) -> u32 {
+        (self.start_ply + self.moves.len() as u32) / 2
+    }
+}
+
+struct XBoard {
+    search: SearchThread,
+    game: Arc<Mutex<Game>>,
+    post: Arc<AtomicBool>,
+    abandon_search: Arc<AtomicBool>,
+    engine_white: Option<bool>, // none in force mode
+
+    moves_per_period: u32,
+    base_time: u32, // ms
+    increment: u32, // ms
+    engine_time: Option<u32>, // ms
+    move_time: Option<u32>, // ms
+    max_depth: Option<i32>,
+}
+
+impl XBoard {
+    fn new() -> XBoard {
+        let post = Arc::new(AtomicBool::new(false));
+        let mut engine = Engine::new();
+        engine.set_info_sink(Box::new(XBoardInfoSink { post: post.clone() }));
+        engine.parse_fen(STARTPOS_FEN).unwrap();
+
+        XBoard {
+            search: SearchThread::new(engine),
+            game: Arc::new(Mutex::new(Game::new(STARTPOS_FEN))),
+            post,
+            abandon_search: Arc::new(AtomicBool::new(false)),
+            engine_white: Some(false),
+
+            moves_per_period: 0,
+            base_time: 300000,
+            increment: 0,
+            engine_time: None,
+            move_time: None,
+            max_depth: None,
+        }
+    }
+
+    // any command changing the position discards the engine's move if it is still thinking
+    fn engine(&mut self) -> &mut Engine {
+        self.abandon_search.store(true, Ordering::Relaxed);
+        self.search.engine()
+    }
+
+    fn set_position(&mut self, fen: &str) {
+        if let Err(e) = self.engine().parse_fen(fen) {
+            println!("tellusererror Illegal position: {}", e);
+            return;
+        }
+        *self.game.lock().unwrap() = Game::new(fen);
+    }
+
+    fn undo(&mut self, count: usize) {
+        // the search thread must be joined before locking since it records its own move
+        let game = self.game.clone();
+        let engine = self.engine();
+        let mut game = game.lock().unwrap();
+        let remaining = game.moves.len().saturating_sub(count);
+        game.moves.truncate(remaining);
+
+        engine.parse_fen(&game.start_fen).unwrap();
+        for mov in game.moves.iter() {
+            engine.play_move(*mov);
+        }
+    }
+
+    fn user_move(&mut self, mov: &str) {
+        let engine = self.engine();
+        let found = match find_move(engine, mov) {
+            Some(m) => m,
+            None => {
+                println!("Illegal move: {}", mov);
+                return;
+            }
+        };
+
+        engine.play_move(found);
+        let result = game_result(engine);
+        self.game.lock().unwrap().moves.push(found);
+        match result {
+            Some(result) => println!("{}", result),
+            None => self.think_if_engine_turn()
+        }
+    }
+
+    fn think_if_engine_turn(&mut self) {
+        let white_turn = self.engine().white_turn();
+        if self.engine_white == Some(white_turn) {
+            self.think();
+        }
+    }
+
+    // time for the engine's next move from the level, st and time commands
+    fn search_budget(&self) -> SearchBudget {
+        match (self.move_time, self.engine_time) {
+            (Some(move_time), _) => SearchBudget::fixed(move_time),
+            (None, Some(remaining)) => {
+                let moves_played = self.game.lock().unwrap().moves_played();
+                let moves_to_go = if self.moves_per_period > 0 {
+                    Some(self.moves_per_period - moves_played % self.moves_per_period)
+                } else {
+                    None
+                };
+                allocate(remaining, self.increment, moves_to_go)
+            },
+            (None, None) => allocate(self.base_time, self.increment, None)
+        }
+    }
+
+    fn think(&mut self) {
+        // the search thread must be joined before locking since it records its own move
+        self.engine();
+        let budget = self.search_budget();
+        let max_depth = self.max_depth.unwrap_or(30);
+        let engine = self.engine();
+        engine.set_search_budget(budget);
+        engine.update_max_search_depth(max_depth);
+
+        let game = self.game.clone();
+        let abandon_search = self.abandon_search.clone();
+        abandon_search.store(false, Ordering::Relaxed);
+        self.search.start(move |engine: &mut Engine, _stop_signal: &AtomicBool| {
+            if game_result(engine).is_some() {
+                return;
+            }
+
+            engine.find_best_move_iterative();
+            if abandon_search.load(Ordering::Relaxed) {
+                return;
+            }
+
+            let mov = match search_result(engine) {
+                Some(m) => m,
+                None => return
+            };
+            engine.play_move(mov);
+            game.lock().unwrap().moves.push(mov);
+            println!("move {}", mov.to_uci());
+            if let Some(result) = game_result(engine) {
+                println!("{}", result);
+            }
+        });
+    }
+
+    // level MPS BASE INC where BASE is minutes or minutes:seconds
+    fn set_level(&mut self, args: &[&str]) {
+        if args.len() < 3 {
+            return;
+        }
+
+        self.moves_per_period = args[0].parse().unwrap_or(0);
+        let mut base = args[1].split(':');
+        let minutes: u32 = base.next().and_then(|m| m.parse().ok()).unwrap_or(5);
+        let seconds: u32 = base.next().and_then(|s| s.parse().ok()).unwrap_or(0);
+        self.base_time = (minutes * 60 + seconds) * 1000;
+        self.increment = (args[2].parse::<f32>().unwrap_or(0.0) * 1000.0) as u32;
+        self.move_time = None;
+    }
+
+    // st SECONDS, a fixed time for every move until the next level
+    fn set_move_time(&mut self, args: &[&str]) {
+        self.move_time = args.first().and_then(|s| s.parse::<u32>().ok()).map(|s| s * 1000);
+    }
+
+    fn run<I: Iterator<Item = String>>(&mut self, lines: I) {
+        for line in lines {
+            let tokens: Vec<&str> = line.split_whitespace().collect();
+            let (command, args) = match tokens.split_first() {
+                Some((command, args)) => (*command, args),
+                None => continue
+            };
+
+            match command {
+                "xboard" | "accepted" | "rejected" | "random" | "hard" | "easy" | "computer" | "otim" => {},
+                "protover" => println!(
+                    "feature myname=\"Bandersnatch {}\" setboard=1 usermove=1 ping=1 colors=0 sigint=0 sigterm=0 analyze=0 done=1",
+                    env!("CARGO_PKG_VERSION")
+                ),
+                "new" => {
+                    self.set_position(STARTPOS_FEN);
+                    self.engine_white = Some(false);
+                    self.max_depth = None;
+                },
+                "setboard" => self.set_position(&args.join(" ")),
+                "usermove" => match args.first() {
+                    Some(mov) => self.user_move(mov),
+                    None => println!("Error (missing move): usermove")
+                },
+                "go" => {
+                    self.engine_white = Some(self.engine().white_turn());
+                    self.think();
+                },
+                "force" | "result" => {
+                    self.engine();
+                    self.engine_white = None;
+                },
+                "?" => self.search.stop(),
+                "undo" => self.undo(1),
+                "remove" => self.undo(2),
+                "level" => self.set_level(args),
+                "st" => self.set_move_time(args),
+                "sd" => self.max_depth = args.first().and_then(|d| d.parse().ok()),
+                "time" => self.engine_time = args.first().and_then(|t| t.parse::<u32>().ok()).map(|t| t * 10),
+                "post" => self.post.store(true, Ordering::Relaxed),
+                "nopost" => self.post.store(false, Ordering::Relaxed),
+                "ping" => {
+                    // the reply has to follow the move being thought about
+                    self.search.wait();
+                    println!("pong {}", args.first().unwrap_or(&""));
+                },
+                "quit" => break,
+                _ => {
+                    // accept bare moves in case the usermove feature was rejected
+                    // looking one up needs the engine, which must not cut short a search over an unknown command
+                    if !self.search.is_searching() && find_move(self.search.engine(), command).is_some() {
+                        self.user_move(command);
+                    } else {
+                        println!("Error (unknown command): {}", command);
+                    }
+                }
+            }
+        }
+
+        self.engine();
+    }
+}
+
+pub fn run<I: Iterator<Item = String>>(lines: I) {
+    XBoard::new().run(lines);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn periods_count_from_the_fullmove_number() {
+        let mut xboard = XBoard::new();
+        xboard.engine_white = None;
+        xboard.set_level(&["40", "10", "0"]);
+        xboard.engine_time = Some(60000);
+        assert_eq!(xboard.search_budget(), allocate(60000, 0, Some(40)));
+
+        // black has made 24 moves, so 16 are left in the period
+        xboard.set_position("4k3/8/8/8/8/8/8/4K3 b - - 0 25");
+        assert_eq!(xboard.search_budget(), allocate(60000, 0, Some(16)));
+
+        xboard.user_move("e8d7");
+        xboard.user_move("e1d2");
+        assert_eq!(xboard.search_budget(), allocate(60000, 0, Some(15)));
+    }
+
+    #[test]
+    fn level_sets_the_time_control() {
+        let mut xboard = XBoard::new();
+        xboard.set_level(&["0", "2:30", "1.5"]);
+        assert_eq!((xboard.moves_per_period, xboard.base_time, xboard.increment), (0, 150000, 1500));
+        // before the first time command the whole base time is assumed to be left
+        assert_eq!(xboard.search_budget(), allocate(150000, 1500, None));
+
+        xboard.set_level(&["40", "5", "0"]);
+        assert_eq!((xboard.moves_per_period, xboard.base_time, xboard.increment), (40, 300000, 0));
+
+        // too few arguments leave the level as it was
+        xboard.set_level(&["30", "1"]);
+        assert_eq!(xboard.moves_per_period, 40);
+    }
+
+    #[test]
+    fn st_fixes_the_time_until_the_next_level() {
+        let mut xboard = XBoard::new();
+        xboard.engine_time = Some(60000);
+        xboard.set_move_time(&["3"]);
+        assert_eq!(xboard.search_budget(), SearchBudget::fixed(3000));
+
+        xboard.set_level(&["0", "1", "0"]);
+        assert_eq!(xboard.search_budget(), allocate(60000, 0, None));
+    }
+
+    #[test]
+    fn sd_limits_the_search_depth() {
+        let lines = ["sd 2", "new", "sd 3", "go", "ping 1", "quit"];
+        let mut xboard = XBoard::new();
+        xboard.run(lines.iter().map(|l| l.to_string()));
+        assert_eq!(xboard.max_depth, Some(3));
+        assert_eq!(xboard.engine().depth_searched_last_turn(), 3);
+    }
+}
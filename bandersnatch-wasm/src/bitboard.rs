@@ -0,0 +1,281 @@
+use std::sync::OnceLock;
+
+// one bit per square, using the same indexing as the board (0 = a8, 63 = h1)
+pub type Bitboard = u64;
+
+pub const EMPTY: Bitboard = 0;
+pub const RANK_1: Bitboard = 0xFF00_0000_0000_0000;
+pub const RANK_8: Bitboard = 0x0000_0000_0000_00FF;
+
+pub fn square_bb(index: usize) -> Bitboard {
+    1 << index
+}
+
+pub fn has_square(bb: Bitboard, index: usize) -> bool {
+    bb & square_bb(index) != 0
+}
+
+pub fn lsb(bb: Bitboard) -> usize {
+    bb.trailing_zeros() as usize
+}
+
+// iterate the set squares of a bitboard from lowest to highest
+pub struct Squares(Bitboard);
+
+impl Iterator for Squares {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        if self.0 == 0 {
+            return None;
+        }
+        let index = lsb(self.0);
+        self.0 &= self.0 - 1;
+        Some(index)
+    }
+}
+
+pub fn squares(bb: Bitboard) -> Squares {
+    Squares(bb)
+}
+
+const ROOK_DIRECTIONS: [(i32, i32); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+const BISHOP_DIRECTIONS: [(i32, i32); 4] = [(1, 1), (-1, 1), (1, -1), (-1, -1)];
+const KNIGHT_OFFSETS: [(i32, i32); 8] = [(1, 2), (2, 1), (2, -1), (1, -2), (-1, -2), (-2, -1), (-2, 1), (-1, 2)];
+const KING_OFFSETS: [(i32, i32); 8] = [(1, 0), (1, 1), (0, 1), (-1, 1), (-1, 0), (-1, -1), (0, -1), (1, -1)];
+
+fn offset_square(index: usize, dx: i32, dy: i32) -> Option<usize> {
+    let x = (index % 8) as i32 + dx;
+    let y = (index / 8) as i32 + dy;
+    if (0..8).contains(&x) && (0..8).contains(&y) {
+        Some((y * 8 + x) as usize)
+    } else {
+        None
+    }
+}
+
+// slow reference implementation used to fill the magic tables
+fn trace_slider_attacks(index: usize, occupancy: Bitboard, directions: &[(i32, i32)]) -> Bitboard {
+    let mut attacks = EMPTY;
+    for &(dx, dy) in directions {
+        let mut current = index;
+        while let Some(next) = offset_square(current, dx, dy) {
+            attacks |= square_bb(next);
+            if has_square(occupancy, next) {
+                break;
+            }
+            current = next;
+        }
+    }
+    attacks
+}
+
+// relevant occupancy bits: every ray square except the last one on the edge
+fn slider_mask(index: usize, directions: &[(i32, i32)]) -> Bitboard {
+    let mut mask = EMPTY;
+    for &(dx, dy) in directions {
+        let mut current = index;
+        while let Some(next) = offset_square(current, dx, dy) {
+            if offset_square(next, dx, dy).is_none() {
+                break;
+            }
+            mask |= square_bb(next);
+            current = next;
+        }
+    }
+    mask
+}
+
+#[derive(Clone, Copy, Default)]
+struct Magic {
+    mask: Bitboard,
+    magic: u64,
+    shift: u32,
+    offset: usize,
+}
+
+impl Magic {
+    fn index(&self, occupancy: Bitboard) -> usize {
+        self.offset + (((occupancy & self.mask).wrapping_mul(self.magic)) >> self.shift) as usize
+    }
+}
+
+// magic multipliers for this board indexing, found once with a sparse random search
+const ROOK_MAGICS: [u64; 64] = [
+    0x1080_0040_0880_1020, 0x0840_0920_02C0_3000, 0x1900_2000_1040_0900, 0x0880_1000_0800_0480,
+    0x4200_1004_2008_0200, 0x8100_0201_0008_0400, 0x0200_0401_1088_6200, 0x0200_0080_4022_0411,
+    0x0404_8000_8440_0220, 0x0000_4010_0040_2000, 0x0086_0010_8122_0440, 0x0408_8008_0010_0280,
+    0x000A_0012_0104_0820, 0x8848_8002_0084_0080, 0x4001_0001_0004_0200, 0x0442_0001_0210_5084,
+    0x9080_0100_2080_4100, 0x0040_4040_0020_1009, 0x0000_8080_1000_2009, 0x2200_0900_21D0_0100,
+    0x0008_0080_0804_0080, 0x0004_0040_0201_0040, 0x0011_0400_0801_5042, 0x0000_0A00_0176_8104,
+    0x0000_8000_8020_4009, 0x2010_0041_4000_2001, 0x9800_2002_8010_0080, 0x1000_1000_8008_0080,
+    0x0442_000A_0004_9020, 0x2100_0400_8002_0080, 0x0800_1204_0090_0148, 0x0010_040A_0012_8541,
+    0x2800_8040_0080_0030, 0x1010_0020_0040_0041, 0x4000_2000_1100_4100, 0x0610_0084_1080_0800,
+    0x0400_8024_0280_0800, 0xC100_0200_8080_0400, 0x0002_0008_0200_0401, 0x0182_0858_8200_0401,
+    0x0220_2040_0080_8000, 0x2860_1000_4002_4022, 0x0001_0020_0411_0040, 0x9910_1042_000A_0020,
+    0x0004_0800_0400_8080, 0x0010_0400_0200_8080, 0x2012_0048_8102_0004, 0x8300_8424_4482_0011,
+    0x0088_4038_8201_0200, 0x0820_4000_8021_0100, 0x0110_9100_40A0_0300, 0x0801_1002_8008_0480,
+    0x0242_0090_0820_0600, 0x1002_0004_8950_0200, 0x0040_8002_0001_0080, 0x0091_8000_4100_0080,
+    0x0000_2093_0048_8001, 0x04C1_0024_1482_4001, 0x0200_2000_0B00_1041, 0x7000_1000_0420_0901,
+    0x8002_0020_0410_0802, 0x3001_0002_084C_0007, 0x0888_2218_0081_3004, 0x4000_0028_4084_0112,
+];
+const BISHOP_MAGICS: [u64; 64] = [
+    0xA010_0411_0800_3100, 0x0060_8202_0A00_2900, 0x6810_0106_1920_0000, 0x0828_1A05_2000_0408,
+    0x0001_1040_0100_0400, 0x0018_9010_0804_8400, 0x0004_0A02_1024_5280, 0x0002_0021_0808_A402,
+    0x9140_0484_1082_1200, 0x0800_0910_1082_0041, 0x2050_4804_8322_02C0, 0x0100_0914_0108_1000,
+    0x8021_0111_4000_0012, 0x0810_0208_0445_0400, 0x208B_0542_1090_08A2, 0x0080_084A_0804_0204,
+    0x0040_E2A8_0811_244C, 0x2505_0220_0800_8108, 0x0430_2201_0042_0040, 0x010A_0404_2022_0040,
+    0x1105_0002_9040_0000, 0x0093_0012_0082_2120, 0x4000_A620_4804_3004, 0x2801_2004_8A01_5004,
+    0x0060_9000_2A02_0814, 0x4404_2000_2408_00D0, 0x0110_2800_040A_4400, 0x1004_0800_8022_0040,
+    0x0001_0010_1100_4024, 0x0010_0440_0080_5040, 0x0914_0412_0082_0100, 0x0004_8210_1282_1480,
+    0x0024_0405_00C0_5021, 0x0088_6110_0208_0200, 0x0116_080A_0004_0020, 0x4000_0200_8008_0080,
+    0x2450_4501_4084_0040, 0x0000_8802_0148_4100, 0x0222_0204_0402_0092, 0x8081_1106_0000_2E00,
+    0x2842_1011_0500_0801, 0x1100_8090_0800_1025, 0x0002_0202_221C_0400, 0x0422_0140_2200_9020,
+    0x0210_0461_0210_0C00, 0xC004_0080_8202_9102, 0x00AA_4618_0110_1200, 0x0404_0800_8020_1108,
+    0x0205_4210_8C20_5002, 0x0410_5448_0410_0100, 0x0040_9108_4110_0000, 0x0400_2000_4202_1100,
+    0x0000_4204_8504_00C0, 0x0200_1004_10A4_2102, 0x1040_0208_0121_0102, 0x0805_0404_1042_0000,
+    0x2884_8041_3010_0200, 0x800C_2622_0124_2000, 0x1058_0001_9410_8800, 0x0014_2210_5442_0204,
+    0x0104_0000_12A0_2200, 0x0200_8810_0330_0100, 0x0140_4002_0284_0100, 0x0402_0208_0101_0201,
+];
+
+// fancy magic bitboards: each square gets its own shift and a slice of one shared attack table
+fn fill_magics(directions: &[(i32, i32)], magic_numbers: &[u64; 64], table: &mut Vec<Bitboard>) -> [Magic; 64] {
+    let mut magics = [Magic::default(); 64];
+    for (index, entry) in magics.iter_mut().enumerate() {
+        let mask = slider_mask(index, directions);
+        let bits = mask.count_ones();
+        let offset = table.len();
+        table.resize(offset + (1usize << bits), EMPTY);
+        *entry = Magic {
+            mask,
+            magic: magic_numbers[index],
+            shift: 64 - bits,
+            offset,
+        };
+
+        // enumerate every subset of the mask (carry-rippler)
+        let mut subset: Bitboard = 0;
+        loop {
+            let attacks = trace_slider_attacks(index, subset, directions);
+            let table_index = entry.index(subset);
+            debug_assert!(table[table_index] == EMPTY || table[table_index] == attacks);
+            table[table_index] = attacks;
+            subset = subset.wrapping_sub(mask) & mask;
+            if subset == 0 {
+                break;
+            }
+        }
+    }
+
+    magics
+}
+
+pub struct AttackTables {
+    rook_magics: [Magic; 64],
+    bishop_magics: [Magic; 64],
+    slider_table: Vec<Bitboard>,
+    knight: [Bitboard; 64],
+    king: [Bitboard; 64],
+    pawn: [[Bitboard; 64]; 2], // [white][index]
+    between: Vec<Bitboard>, // squares strictly between two aligned squares
+    line: Vec<Bitboard>, // the full line through two aligned squares
+}
+
+impl AttackTables {
+    fn new() -> AttackTables {
+        let mut slider_table: Vec<Bitboard> = vec![];
+        let rook_magics = fill_magics(&ROOK_DIRECTIONS, &ROOK_MAGICS, &mut slider_table);
+        let bishop_magics = fill_magics(&BISHOP_DIRECTIONS, &BISHOP_MAGICS, &mut slider_table);
+
+        let mut knight = [EMPTY; 64];
+        let mut king = [EMPTY; 64];
+        let mut pawn = [[EMPTY; 64]; 2];
+        for index in 0..64 {
+            for &(dx, dy) in KNIGHT_OFFSETS.iter() {
+                if let Some(to) = offset_square(index, dx, dy) {
+                    knight[index] |= square_bb(to);
+                }
+            }
+            for &(dx, dy) in KING_OFFSETS.iter() {
+                if let Some(to) = offset_square(index, dx, dy) {
+                    king[index] |= square_bb(to);
+                }
+            }
+            for dx in [-1, 1] {
+                // white pawns move towards index 0
+                if let Some(to) = offset_square(index, dx, -1) {
+                    pawn[1][index] |= square_bb(to);
+                }
+                if let Some(to) = offset_square(index, dx, 1) {
+                    pawn[0][index] |= square_bb(to);
+                }
+            }
+        }
+
+        let mut between = vec![EMPTY; 64 * 64];
+        let mut line = vec![EMPTY; 64 * 64];
+        for from in 0..64 {
+            for directions in [&ROOK_DIRECTIONS, &BISHOP_DIRECTIONS] {
+                for &(dx, dy) in directions.iter() {
+                    let full_ray = trace_slider_attacks(from, EMPTY, &[(dx, dy), (-dx, -dy)]) | square_bb(from);
+                    let mut squares_between = EMPTY;
+                    let mut current = from;
+                    while let Some(to) = offset_square(current, dx, dy) {
+                        between[from * 64 + to] = squares_between;
+                        line[from * 64 + to] = full_ray;
+                        squares_between |= square_bb(to);
+                        current = to;
+                    }
+                }
+            }
+        }
+
+        AttackTables {
+            rook_magics,
+            bishop_magics,
+            slider_table,
+            knight,
+            king,
+            pawn,
+            between,
+            line,
+        }
+    }
+
+    pub fn get() -> &'static AttackTables {
+        static TABLES: OnceLock<AttackTables> = OnceLock::new();
+        TABLES.get_or_init(AttackTables::new)
+    }
+
+    pub fn rook(&self, index: usize, occupancy: Bitboard) -> Bitboard {
+        self.slider_table[self.rook_magics[index].index(occupancy)]
+    }
+
+    pub fn bishop(&self, index: usize, occupancy: Bitboard) -> Bitboard {
+        self.slider_table[self.bishop_magics[index].index(occupancy)]
+    }
+
+    pub fn queen(&self, index: usize, occupancy: Bitboard) -> Bitboard {
+        self.rook(index, occupancy) | self.bishop(index, occupancy)
+    }
+
+    pub fn knight(&self, index: usize) -> Bitboard {
+        self.knight[index]
+    }
+
+    pub fn king(&self, index: usize) -> Bitboard {
+        self.king[index]
+    }
+
+    pub fn pawn(&self, white: bool, index: usize) -> Bitboard {
+        self.pawn[white as usize][index]
+    }
+
+    pub fn between(&self, from: usize, to: usize) -> Bitboard {
+        self.between[from * 64 + to]
+    }
+
+    pub fn line(&self, from: usize, to: usize) -> Bitboard {
+        self.line[from * 64 + to]
+    }
+}
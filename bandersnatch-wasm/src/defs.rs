@@ -72,19 +72,100 @@ bitflags! {
     }
 }
 
-// setting fields to -1 ignores them
-#[wasm_bindgen]
-#[derive(Copy, Clone)]
-pub struct BoardDelta {
-    pub index: i32, // current location of the piece
-    pub piece: Piece,
-    pub target: i32 // where the piece is moving to (if appliciable)
+pub struct MoveFlag;
+impl MoveFlag {
+    pub const QUIET: u16 = 0;
+    pub const DOUBLE_PAWN_PUSH: u16 = 1;
+    pub const KING_CASTLE: u16 = 2;
+    pub const QUEEN_CASTLE: u16 = 3;
+    pub const CAPTURE: u16 = 4; // also set on capturing promotions
+    pub const EN_PASSANT: u16 = 5;
+    pub const KNIGHT_PROMOTION: u16 = 8;
+    pub const BISHOP_PROMOTION: u16 = 9;
+    pub const ROOK_PROMOTION: u16 = 10;
+    pub const QUEEN_PROMOTION: u16 = 11;
 }
 
-#[wasm_bindgen]
-pub struct MoveInfo {
-    pub index: usize, // to_index
-    pub data: Piece // promotion
+// from (bits 0-5), to (bits 6-11) and a MoveFlag (bits 12-15)
+#[repr(transparent)]
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Move(u16);
+
+impl Move {
+    pub const NULL: Move = Move(0);
+
+    pub fn new(from: usize, to: usize, flags: u16) -> Move {
+        Move(from as u16 | (to as u16) << 6 | flags << 12)
+    }
+
+    pub fn from(self) -> usize {
+        (self.0 & 0x3F) as usize
+    }
+
+    pub fn to(self) -> usize {
+        ((self.0 >> 6) & 0x3F) as usize
+    }
+
+    pub fn flags(self) -> u16 {
+        self.0 >> 12
+    }
+
+    pub fn is_null(self) -> bool {
+        self == Move::NULL
+    }
+
+    pub fn is_capture(self) -> bool {
+        self.flags() & MoveFlag::CAPTURE != 0
+    }
+
+    pub fn is_en_passant(self) -> bool {
+        self.flags() == MoveFlag::EN_PASSANT
+    }
+
+    pub fn is_double_pawn_push(self) -> bool {
+        self.flags() == MoveFlag::DOUBLE_PAWN_PUSH
+    }
+
+    pub fn is_castle(self) -> bool {
+        self.flags() == MoveFlag::KING_CASTLE || self.flags() == MoveFlag::QUEEN_CASTLE
+    }
+
+    pub fn is_promotion(self) -> bool {
+        self.flags() & MoveFlag::KNIGHT_PROMOTION != 0
+    }
+
+    // Piece::Empty for anything but a promotion, the color follows from the promotion rank
+    pub fn promotion_piece(self) -> Piece {
+        if !self.is_promotion() {
+            return Piece::Empty;
+        }
+
+        let piece = match self.flags() & !MoveFlag::CAPTURE {
+            MoveFlag::QUEEN_PROMOTION => Piece::Queen_B,
+            MoveFlag::ROOK_PROMOTION => Piece::Rook_B,
+            MoveFlag::BISHOP_PROMOTION => Piece::Bishop_B,
+            _ => Piece::Knight_B
+        };
+        if self.to() < 8 { Piece::from_num(piece as i32 + 6) } else { piece }
+    }
+}
+
+// moves paired with an ordering or search score
+#[derive(Default, Clone, Copy)]
+pub struct ScoredMove {
+    pub mov: Move,
+    pub score: i32
+}
+
+// state which cannot be recovered from the position after a move, returned by make_move
+#[derive(Clone, Copy)]
+pub struct UndoInfo {
+    pub mov: Move,
+    pub captured_piece: Piece,
+    pub castle_status: CastleStatus,
+    pub en_passant_square: i32,
+    pub move_rep_count: i32,
+    pub board_hash: u64
 }
 
 // keep everything as i32 for easy reading in js
@@ -93,8 +174,52 @@ pub struct MoveInfo {
 pub struct EvalMove {
     pub from: i32,
     pub to: i32,
-    pub data: i32,
-    pub score: i32
+    pub data: i32, // promotion piece
+    pub score: i32,
+    pub flags: i32
+}
+
+impl EvalMove {
+    pub fn new(mov: Move, score: i32) -> EvalMove {
+        EvalMove {
+            from: mov.from() as i32,
+            to: mov.to() as i32,
+            data: mov.promotion_piece() as i32,
+            score,
+            flags: mov.flags() as i32
+        }
+    }
+}
+
+// reasons a move from the human move api is rejected
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoveError {
+    MissingPromotion,
+    InvalidPromotion(Piece),
+}
+
+impl std::fmt::Display for MoveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            MoveError::MissingPromotion => write!(f, "a promoting move needs a promotion piece"),
+            MoveError::InvalidPromotion(piece) => write!(f, "cannot promote to {:?} with this move", piece),
+        }
+    }
+}
+
+impl std::error::Error for MoveError {}
+
+impl From<MoveError> for JsValue {
+    fn from(error: MoveError) -> JsValue {
+        JsError::new(&error.to_string()).into()
+    }
+}
+
+// one root move of a perft divide
+#[derive(Clone, Copy)]
+pub struct PerftEntry {
+    pub mov: Move,
+    pub nodes: u64
 }
 
 #[wasm_bindgen]
@@ -113,17 +238,25 @@ pub enum SavedEvalType {
     Beta = 2
 }
 
-#[wasm_bindgen]
-pub struct EvaluationData {
-    pub total_moves: i32,
-    pub eval: i32,
-    pub best_move: EvalMove,
-    pub depth: i32,
-    pub saved_type: SavedEvalType,
-}
-
 pub const SMALLEST_SAFE_I32: i32 = -2147483000;
 pub const LARGEST_SAFE_I32: i32 = 2147483000;
+// mate scores are offset from the safe bounds by the ply the mate happens at, so no mate is further than this
+pub const MAX_MATE_PLY: i32 = 1000;
+
+pub fn is_mate_score(score: i32) -> bool {
+    score >= LARGEST_SAFE_I32 - MAX_MATE_PLY || score <= SMALLEST_SAFE_I32 + MAX_MATE_PLY
+}
+
+// moves until mate, positive when the side to move mates and negative when it gets mated
+pub fn mate_in(score: i32) -> Option<i32> {
+    if score >= LARGEST_SAFE_I32 - MAX_MATE_PLY {
+        Some((LARGEST_SAFE_I32 - score + 1) / 2)
+    } else if score <= SMALLEST_SAFE_I32 + MAX_MATE_PLY {
+        Some(-(score - SMALLEST_SAFE_I32) / 2)
+    } else {
+        None
+    }
+}
 
 pub const EMPTY_SQUARE_TABLE: [i32; 64] = [
     0,  0,  0,  0,  0,  0,  0,  0,
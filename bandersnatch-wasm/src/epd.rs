@@ -0,0 +1,239 @@
+use std::fmt;
+
+use crate::Engine;
+use crate::defs::{Move, mate_in};
+use crate::fen::FenError;
+use crate::san::SanError;
+use crate::time_control::SearchBudget;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EpdError {
+    FieldCount(usize),
+    InvalidOperation(String),
+    Fen(FenError),
+    Move(SanError),
+}
+
+impl fmt::Display for EpdError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EpdError::FieldCount(count) => write!(f, "expected 4 position fields but found {}", count),
+            EpdError::InvalidOperation(operation) => write!(f, "invalid operation '{}'", operation),
+            EpdError::Fen(error) => write!(f, "{}", error),
+            EpdError::Move(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for EpdError {}
+
+impl From<FenError> for EpdError {
+    fn from(error: FenError) -> EpdError {
+        EpdError::Fen(error)
+    }
+}
+
+impl From<SanError> for EpdError {
+    fn from(error: SanError) -> EpdError {
+        EpdError::Move(error)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EpdPosition {
+    pub fen: String, // move counters come from hmvc and fmvn when present
+    pub id: Option<String>,
+    pub bm: Vec<Move>,
+    pub am: Vec<Move>,
+    pub c0: Option<String>,
+    pub dm: Option<i32>,
+    pub hmvc: Option<u32>,
+    pub fmvn: Option<u32>,
+    pub operations: Vec<(String, Vec<String>)>, // every opcode in order, including unknown ones
+}
+
+impl EpdPosition {
+    // a best move must be found, no avoid move may be played and a direct mate must be seen within dm moves
+    // mate is the moves to mate found by the search, as mate_in gives it
+    pub fn is_solved_by(&self, mov: Move, mate: Option<i32>) -> bool {
+        if self.bm.is_empty() && self.am.is_empty() && self.dm.is_none() {
+            return false;
+        }
+        let mate_found = self.dm.is_none_or(|dm| mate.is_some_and(|m| m > 0 && m <= dm));
+        (self.bm.is_empty() || self.bm.contains(&mov)) && !self.am.contains(&mov) && mate_found
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchLimit {
+    Depth(i32),
+    Time(u32), // ms
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EpdResult {
+    pub id: Option<String>,
+    pub best_move: Move,
+    pub san: String,
+    pub solved: bool,
+    pub mate: Option<i32>, // moves to mate, negative when the side to move gets mated
+    pub depth: i32,
+    pub time: u32, // ms
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EpdReport {
+    pub results: Vec<EpdResult>,
+}
+
+impl EpdReport {
+    pub fn solved(&self) -> usize {
+        self.results.iter().filter(|r| r.solved).count()
+    }
+
+    pub fn total_time(&self) -> u32 {
+        self.results.iter().map(|r| r.time).sum()
+    }
+}
+
+// splits the operations after the position fields, quoted operands may contain spaces and semicolons
+fn parse_operations(text: &str) -> Result<Vec<(String, Vec<String>)>, EpdError> {
+    let mut operations = vec![];
+    let mut words: Vec<String> = vec![];
+    let mut word = String::new();
+    let mut in_quotes = false;
+    let mut quoted = false;
+
+    for c in text.chars().chain(std::iter::once(';')) {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                quoted = true;
+            },
+            c if in_quotes => word.push(c),
+            c if c.is_whitespace() || c == ';' => {
+                if !word.is_empty() || quoted {
+                    words.push(word.clone());
+                    word.clear();
+                    quoted = false;
+                }
+                if c == ';' && !words.is_empty() {
+                    let opcode = words.remove(0);
+                    if !opcode.chars().next().unwrap_or(' ').is_ascii_alphabetic() {
+                        return Err(EpdError::InvalidOperation(opcode));
+                    }
+                    operations.push((opcode, words.clone()));
+                    words.clear();
+                }
+            },
+            c => word.push(c)
+        }
+    }
+
+    if in_quotes {
+        return Err(EpdError::InvalidOperation(String::from(text.trim())));
+    }
+
+    Ok(operations)
+}
+
+fn single_operand<T: std::str::FromStr>(opcode: &str, operands: &[String]) -> Result<T, EpdError> {
+    match operands {
+        [operand] => operand.parse().map_err(|_| EpdError::InvalidOperation(format!("{} {}", opcode, operand))),
+        _ => Err(EpdError::InvalidOperation(format!("{} {}", opcode, operands.join(" "))))
+    }
+}
+
+impl Engine {
+    // sets up the position and parses its operations, move operands are san
+    pub fn parse_epd(&mut self, epd: &str) -> Result<EpdPosition, EpdError> {
+        let epd = epd.trim();
+        let mut fields = vec![];
+        let mut rest = epd;
+        while fields.len() < 4 {
+            let trimmed = rest.trim_start();
+            let end = trimmed.find(char::is_whitespace).unwrap_or(trimmed.len());
+            if end == 0 {
+                break;
+            }
+            fields.push(&trimmed[..end]);
+            rest = &trimmed[end..];
+        }
+        if fields.len() != 4 {
+            return Err(EpdError::FieldCount(fields.len()));
+        }
+
+        let mut position = EpdPosition {
+            operations: parse_operations(rest)?,
+            ..Default::default()
+        };
+        for (opcode, operands) in position.operations.iter() {
+            match opcode.as_str() {
+                "id" => position.id = operands.first().cloned(),
+                "c0" => position.c0 = operands.first().cloned(),
+                "dm" => position.dm = Some(single_operand(opcode, operands)?),
+                "hmvc" => position.hmvc = Some(single_operand(opcode, operands)?),
+                "fmvn" => position.fmvn = Some(single_operand(opcode, operands)?),
+                _ => {}
+            }
+        }
+
+        position.fen = format!("{} {} {}", fields.join(" "), position.hmvc.unwrap_or(0), position.fmvn.unwrap_or(1));
+        self.parse_fen(&position.fen)?;
+
+        for (opcode, operands) in position.operations.iter() {
+            let moves = match opcode.as_str() {
+                "bm" => &mut position.bm,
+                "am" => &mut position.am,
+                _ => continue
+            };
+            for san in operands.iter() {
+                moves.push(self.move_from_san(san)?);
+            }
+        }
+
+        Ok(position)
+    }
+
+    // searches every position with the same limit and checks the result against bm, am and dm
+    pub fn run_epd(&mut self, positions: &[EpdPosition], limit: SearchLimit) -> EpdReport {
+        let budget = self.search_budget;
+        let max_depth = self.search_max_depth;
+        match limit {
+            SearchLimit::Depth(depth) => {
+                self.search_budget = SearchBudget::fixed(u32::MAX);
+                self.search_max_depth = depth;
+            },
+            SearchLimit::Time(time) => self.search_budget = SearchBudget::fixed(time)
+        }
+
+        let mut report = EpdReport::default();
+        for position in positions.iter() {
+            if self.parse_fen(&position.fen).is_err() {
+                continue;
+            }
+            self.transposition_table.clear();
+            self.best_move = Default::default();
+
+            let start_time = self.clock.now();
+            self.find_best_move_iterative();
+            let time = self.clock.now() - start_time;
+
+            let best_move = self.best_move.mov;
+            let mate = mate_in(self.best_move.score).filter(|_| !best_move.is_null());
+            report.results.push(EpdResult {
+                id: position.id.clone(),
+                best_move,
+                san: if best_move.is_null() { String::from("-") } else { self.move_to_san(best_move) },
+                solved: position.is_solved_by(best_move, mate),
+                mate,
+                depth: self.depth_searched_last_turn,
+                time,
+            });
+        }
+
+        self.search_budget = budget;
+        self.search_max_depth = max_depth;
+        report
+    }
+}
@@ -0,0 +1,58 @@
+use std::fmt;
+
+use wasm_bindgen::prelude::*;
+
+pub const STARTPOS_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+// reasons a fen string is rejected, squares are given in algebraic notation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FenError {
+    FieldCount(usize),
+    RankCount(usize),
+    RankLength { rank: usize, length: usize },
+    InvalidPiece(char),
+    MissingKing { white: bool },
+    TooManyKings { white: bool },
+    PawnOnBackRank(String),
+    InvalidSideToMove(String),
+    InvalidCastling(String),
+    CastlingRightsMismatch(char),
+    InvalidEnPassant(String),
+    ImpossibleEnPassant(String),
+    InvalidMoveCounter(String),
+    OpponentInCheck,
+}
+
+fn color_name(white: bool) -> &'static str {
+    if white { "white" } else { "black" }
+}
+
+impl fmt::Display for FenError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FenError::FieldCount(count) => write!(f, "expected 4 or 6 fields but found {}", count),
+            FenError::RankCount(count) => write!(f, "expected 8 ranks but found {}", count),
+            FenError::RankLength { rank, length } => write!(f, "rank {} describes {} squares instead of 8", rank, length),
+            FenError::InvalidPiece(c) => write!(f, "'{}' is not a piece", c),
+            FenError::MissingKing { white } => write!(f, "{} has no king", color_name(*white)),
+            FenError::TooManyKings { white } => write!(f, "{} has more than one king", color_name(*white)),
+            FenError::PawnOnBackRank(square) => write!(f, "pawn on the back rank at {}", square),
+            FenError::InvalidSideToMove(side) => write!(f, "side to move must be 'w' or 'b', not '{}'", side),
+            FenError::InvalidCastling(castling) => write!(f, "invalid castling field '{}'", castling),
+            FenError::CastlingRightsMismatch(right) => write!(f, "castling right '{}' does not match the king and rook placement", right),
+            FenError::InvalidEnPassant(square) => write!(f, "invalid en passant square '{}'", square),
+            FenError::ImpossibleEnPassant(square) => write!(f, "en passant square {} does not follow a double pawn push", square),
+            FenError::InvalidMoveCounter(counter) => write!(f, "invalid move counter '{}'", counter),
+            FenError::OpponentInCheck => write!(f, "the side not to move is in check"),
+        }
+    }
+}
+
+impl std::error::Error for FenError {}
+
+// thrown as an Error on the js side
+impl From<FenError> for JsValue {
+    fn from(error: FenError) -> JsValue {
+        JsError::new(&error.to_string()).into()
+    }
+}
@@ -0,0 +1,227 @@
+use crate::defs::{Move, UndoInfo};
+use crate::fen::STARTPOS_FEN;
+use crate::pgn::{PgnGame, PgnMove};
+
+pub type NodeId = usize;
+
+// one move of the game tree, the first child continues the mainline and the others are sidelines
+#[derive(Clone, Debug, Default)]
+pub struct GameNode {
+    pub mov: Move, // null for the root
+    pub comment: Option<String>,
+    pub comment_before: Option<String>, // only written when the node starts a variation
+    pub nags: Vec<u8>,
+    parent: Option<NodeId>,
+    children: Vec<NodeId>,
+}
+
+impl GameNode {
+    pub fn parent(&self) -> Option<NodeId> {
+        self.parent
+    }
+
+    pub fn children(&self) -> &[NodeId] {
+        &self.children
+    }
+}
+
+// every move tried from a start position, plus undo records for the path to the current node
+// deleted branches stay in the arena but are unreachable
+#[derive(Clone)]
+pub struct Game {
+    pub tags: Vec<(String, String)>, // kept from an imported pgn
+    pub result: String,
+    start_fen: String,
+    nodes: Vec<GameNode>,
+    current: NodeId,
+    undos: Vec<UndoInfo>,
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Game::new(STARTPOS_FEN)
+    }
+}
+
+impl Game {
+    pub const ROOT: NodeId = 0;
+
+    pub fn new(start_fen: &str) -> Game {
+        Game {
+            tags: vec![],
+            result: String::from("*"),
+            start_fen: String::from(start_fen),
+            nodes: vec![GameNode::default()],
+            current: Game::ROOT,
+            undos: vec![],
+        }
+    }
+
+    // the tree of a parsed game, positioned at the root
+    pub fn from_pgn(pgn: &PgnGame) -> Game {
+        let mut game = Game::new(pgn.start_fen());
+        game.tags = pgn.tags.clone();
+        game.result = pgn.result.clone();
+        game.nodes[Game::ROOT].comment = pgn.mainline.comment.clone();
+        game.add_pgn_moves(Game::ROOT, &pgn.mainline.moves);
+        game
+    }
+
+    // returns the node of the first move
+    fn add_pgn_moves(&mut self, parent: NodeId, moves: &[PgnMove]) -> Option<NodeId> {
+        let mut first = None;
+        let mut parent = parent;
+        for mov in moves.iter() {
+            let node = self.add_child(parent, mov.mov);
+            self.nodes[node].comment = mov.comment.clone();
+            self.nodes[node].nags = mov.nags.clone();
+            first = first.or(Some(node));
+
+            // variations are alternatives to this move, so they hang off the same parent
+            for variation in mov.variations.iter() {
+                if let Some(sideline) = self.add_pgn_moves(parent, &variation.moves) {
+                    self.nodes[sideline].comment_before = variation.comment.clone();
+                }
+            }
+            parent = node;
+        }
+        first
+    }
+
+    fn add_child(&mut self, parent: NodeId, mov: Move) -> NodeId {
+        let id = self.nodes.len();
+        self.nodes.push(GameNode {
+            mov,
+            parent: Some(parent),
+            ..Default::default()
+        });
+        self.nodes[parent].children.push(id);
+        id
+    }
+
+    pub fn start_fen(&self) -> &str {
+        &self.start_fen
+    }
+
+    pub fn current(&self) -> NodeId {
+        self.current
+    }
+
+    pub fn node(&self, id: NodeId) -> &GameNode {
+        &self.nodes[id]
+    }
+
+    pub fn node_mut(&mut self, id: NodeId) -> &mut GameNode {
+        &mut self.nodes[id]
+    }
+
+    // false for deleted branches and ids that were never handed out
+    pub fn contains(&self, id: NodeId) -> bool {
+        let mut id = id;
+        while id != Game::ROOT {
+            match self.nodes.get(id).and_then(|n| n.parent) {
+                Some(parent) if self.nodes[parent].children.contains(&id) => id = parent,
+                _ => return false
+            }
+        }
+        true
+    }
+
+    // nodes from the first move down to id
+    pub fn path(&self, id: NodeId) -> Vec<NodeId> {
+        let mut path = vec![];
+        let mut id = id;
+        while let Some(parent) = self.nodes[id].parent {
+            path.push(id);
+            id = parent;
+        }
+        path.reverse();
+        path
+    }
+
+    pub fn is_ancestor(&self, ancestor: NodeId, id: NodeId) -> bool {
+        ancestor == id || self.path(id).contains(&ancestor)
+    }
+
+    // the line through the current node, continuing along the mainline after it
+    pub fn line(&self) -> Vec<NodeId> {
+        let mut line = self.path(self.current);
+        let mut id = self.current;
+        while let Some(child) = self.nodes[id].children.first() {
+            line.push(*child);
+            id = *child;
+        }
+        line
+    }
+
+    // the moves of line(), including moves that were undone
+    pub fn moves(&self) -> Vec<Move> {
+        self.line().iter().map(|id| self.nodes[*id].mov).collect()
+    }
+
+    // the moves leading to the current position
+    pub fn played_moves(&self) -> Vec<Move> {
+        self.path(self.current).iter().map(|id| self.nodes[*id].mov).collect()
+    }
+
+    pub fn undos(&self) -> &[UndoInfo] {
+        &self.undos
+    }
+
+    pub fn ply(&self) -> usize {
+        self.undos.len()
+    }
+
+    pub fn len(&self) -> usize {
+        self.line().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes[Game::ROOT].children.is_empty()
+    }
+
+    pub fn next_move(&self) -> Option<Move> {
+        self.nodes[self.current].children.first().map(|id| self.nodes[*id].mov)
+    }
+
+    // records a move that was just made, a move not tried here before becomes a new sideline
+    pub fn push(&mut self, mov: Move, undo: UndoInfo) {
+        let existing = self.nodes[self.current].children.iter().find(|id| self.nodes[**id].mov == mov).copied();
+        self.current = match existing {
+            Some(id) => id,
+            None => self.add_child(self.current, mov)
+        };
+        self.undos.push(undo);
+    }
+
+    // steps back one ply, the caller unmakes the returned move
+    pub fn pop(&mut self) -> Option<UndoInfo> {
+        let undo = self.undos.pop()?;
+        self.current = self.nodes[self.current].parent.unwrap();
+        Some(undo)
+    }
+
+    // forgets every move after the current node
+    pub fn truncate(&mut self) {
+        self.nodes[self.current].children.clear();
+    }
+
+    // makes the line through id the mainline at every branch point above it
+    pub fn promote_to_mainline(&mut self, id: NodeId) {
+        for node in self.path(id) {
+            let parent = self.nodes[node].parent.unwrap();
+            let children = &mut self.nodes[parent].children;
+            let index = children.iter().position(|c| *c == node).unwrap();
+            let promoted = children.remove(index);
+            children.insert(0, promoted);
+        }
+    }
+
+    // removes id and everything after it, the current node must not be inside the branch
+    pub fn delete_branch(&mut self, id: NodeId) {
+        if let Some(parent) = self.nodes[id].parent {
+            self.nodes[parent].children.retain(|c| *c != id);
+            self.nodes[id].parent = None;
+        }
+    }
+}
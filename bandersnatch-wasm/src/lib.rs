@@ -1,12 +1,44 @@
-mod defs;
+pub mod bitboard;
+pub mod defs;
+pub mod epd;
+pub mod fen;
+pub mod game;
+pub mod pgn;
+pub mod platform;
+pub mod pv;
+pub mod san;
+pub mod status;
+pub mod time_control;
+pub mod transposition;
+pub mod uci;
 
 use defs::EMPTY_SQUARE_TABLE;
 use wasm_bindgen::prelude::*;
-use std::{cmp::{max, min}, intrinsics::transmute, mem::swap, usize, vec};
+use std::{cmp::{Reverse, max, min}, mem::{replace, swap}, sync::{Arc, atomic::{AtomicBool, Ordering}}, vec};
 
 use rand::Rng;
 
-use crate::defs::{BISHOP_SQUARE_TABLE, BoardDelta, CastleStatus, DebugMoveOutput, EvalMove, EvaluationData, KING_MIDDLE_GAME_SQUARE_TABLE, KNIGHT_SQUARE_TABLE, LARGEST_SAFE_I32, MoveInfo, PAWN_SQUARE_TABLE, Piece, QUEEN_SQUARE_TABLE, ROOK_SQUARE_TABLE, SMALLEST_SAFE_I32, SavedEvalType, Value, set_panic_hook};
+use crate::bitboard::{AttackTables, Bitboard, EMPTY, RANK_1, RANK_8, has_square, lsb, square_bb, squares};
+use crate::fen::FenError;
+use crate::game::{Game, NodeId};
+use crate::pgn::{PgnError, PgnGame};
+use crate::san::SanError;
+use crate::status::GameStatus;
+use crate::time_control::{CLEAR_MARGIN, ChessClock, SearchBudget, TimeControl, TimeManager};
+use crate::transposition::{DEFAULT_HASH_SIZE, TranspositionTable};
+use crate::uci::UciError;
+use crate::platform::{Clock, InfoSink, LogSink, SearchInfo};
+use crate::pv::{PvLine, PvTable};
+
+use crate::defs::{BISHOP_SQUARE_TABLE, CastleStatus, DebugMoveOutput, EvalMove, KING_MIDDLE_GAME_SQUARE_TABLE, KNIGHT_SQUARE_TABLE, LARGEST_SAFE_I32, Move, MoveError, MoveFlag, PAWN_SQUARE_TABLE, PerftEntry, Piece, QUEEN_SQUARE_TABLE, ROOK_SQUARE_TABLE, SMALLEST_SAFE_I32, SavedEvalType, ScoredMove, UndoInfo, Value, is_mate_score, mate_in, set_panic_hook};
+
+// depth taken off the search after a null move, on top of the move itself
+const NULL_MOVE_REDUCTION: i32 = 2;
+// moves searched at full depth before the rest of the quiet moves are reduced
+const LMR_FULL_DEPTH_MOVES: usize = 3;
+const LMR_MIN_DEPTH: i32 = 3;
+// half width of the first root window around the last iteration's score, grown 4x after each miss
+const ASPIRATION_WINDOW: i32 = 50;
 
 // #[global_allocator]
 // static ALLOC: wee_alloc::WeeAlloc = wee_alloc::WeeAlloc::INIT;
@@ -14,17 +46,6 @@ use crate::defs::{BISHOP_SQUARE_TABLE, BoardDelta, CastleStatus, DebugMoveOutput
 #[wasm_bindgen]
 extern "C" {
     fn alert(s: &str);
-
-    #[wasm_bindgen(js_namespace = console)]
-    fn log(s: &str);
-
-    // workaround to perf.now() in webworkers
-    type Date;
-    #[wasm_bindgen(static_method_of = Date)]
-    fn now() -> u32;
-
-    #[wasm_bindgen(js_namespace = self)]
-    fn post_eval_message(s: String, eval: i32);
 }
 
 #[wasm_bindgen]
@@ -40,6 +61,10 @@ pub struct Engine {
     zobrist_hash_table: Vec<Vec<u64>>,
 
     board: [Piece; 64],
+    bitboards: [Bitboard; 13], // indexed by piece
+    color_occupancy: [Bitboard; 2], // [white]
+    occupancy: Bitboard,
+    attack_tables: &'static AttackTables,
     board_hash: u64,
     white_turn: bool,
     castle_status: CastleStatus,
@@ -50,24 +75,40 @@ pub struct Engine {
     thread_count: i32,
     thread_index: i32,
 
-    board_deltas: Vec<BoardDelta>,
-    piece_locations: Vec<Vec<usize>>,
-    pinned_pieces: Vec<usize>,
     repetition_history: Vec<u64>,
-    saved_evaluations: hashbrown::HashMap<u64, EvaluationData>,
+    game: Game,
+    transposition_table: TranspositionTable,
 
     castled_this_turn: bool,
+    last_move_san: String,
     piece_captured_this_turn: bool,
     in_check: bool,
-    all_valid_moves: Vec<EvalMove>,
-    best_move: EvalMove,
-    best_move_this_iteration: EvalMove,
+    all_valid_moves: Vec<Move>,
+    best_move: ScoredMove,
+    best_move_this_iteration: ScoredMove,
+    last_iteration_best: Move, // null before the first iteration completes
+    best_move_changes: usize, // times the root best move was replaced in the current aspiration attempt
+    pv_table: PvTable,
+    principal_variation: Vec<Move>, // of the last completed iteration
+    multi_pv: usize,
+    pv_lines: Vec<PvLine>, // best first
+    excluded_root_moves: Vec<Move>, // root moves already reported by a multipv search at this depth
     search_start_time: u32,
-    search_max_time: u32,
+    search_budget: SearchBudget,
+    search_max_depth: i32,
+    chess_clock: Option<ChessClock>, // none when every move gets the fixed search budget
+    turn_start_time: u32,
+    stop_signal: Arc<AtomicBool>,
     time_taken_last_turn: u32,
     depth_searched_last_turn: i32,
+    nodes_searched: u64, // by the current or last search, quiescence included
+    after_null_move: bool, // the move leading to the node being entered was a null move
     moves_found_this_turn: Vec<DebugMoveOutput>,
     moves_found_this_iteration: Vec<DebugMoveOutput>,
+
+    clock: Box<dyn Clock>,
+    log_sink: Box<dyn LogSink>,
+    info_sink: Box<dyn InfoSink>,
 }
 
 #[wasm_bindgen]
@@ -124,6 +165,10 @@ impl Engine {
             zobrist_hash_table: zobrist_hashes,
 
             board: [Piece::Empty; 64],
+            bitboards: [EMPTY; 13],
+            color_occupancy: [EMPTY; 2],
+            occupancy: EMPTY,
+            attack_tables: AttackTables::get(),
             board_hash: 0,
             white_turn: true,
             castle_status: CastleStatus::UNSET,
@@ -134,73 +179,81 @@ impl Engine {
             thread_count: 1,
             thread_index: 0,
 
-            board_deltas: vec![],
-            piece_locations: vec![
-                vec![],
-                vec![],
-                vec![],
-                vec![],
-                vec![],
-                vec![],
-                vec![],
-                vec![],
-                vec![],
-                vec![],
-                vec![],
-                vec![],
-                vec![],
-                vec![],
-                vec![]
-            ],
-            pinned_pieces: vec![],
             repetition_history: vec![],
-            saved_evaluations: hashbrown::HashMap::new(),
+            game: Game::default(),
+            transposition_table: TranspositionTable::new(DEFAULT_HASH_SIZE),
 
             castled_this_turn: false,
+            last_move_san: String::new(),
             piece_captured_this_turn: false,
             in_check: false,
             all_valid_moves: vec![],
             best_move: Default::default(),
             best_move_this_iteration: Default::default(),
+            last_iteration_best: Move::NULL,
+            best_move_changes: 0,
+            pv_table: PvTable::new(),
+            principal_variation: vec![],
+            multi_pv: 1,
+            pv_lines: vec![],
+            excluded_root_moves: vec![],
             search_start_time: 0,
-            search_max_time: 3000,
+            search_budget: SearchBudget::fixed(3000),
+            search_max_depth: 30,
+            chess_clock: None,
+            turn_start_time: 0,
+            stop_signal: Arc::new(AtomicBool::new(false)),
             time_taken_last_turn: 0,
             depth_searched_last_turn: 0,
+            nodes_searched: 0,
+            after_null_move: false,
             moves_found_this_turn: vec![],
             moves_found_this_iteration: vec![],
+
+            clock: platform::default_clock(),
+            log_sink: platform::default_log_sink(),
+            info_sink: platform::default_info_sink(),
         }
     }
 
-    fn remove_piece(&mut self, piece: Piece, index: usize) {
-        let piece_as_index = piece as usize;
-        match self.piece_locations[piece_as_index]
-            .iter()
-            .position(|&r| r == index) {
-                Some(i) => {
-                    self.piece_locations[piece_as_index][i] = *self.piece_locations[piece_as_index].last().unwrap();
-                    self.piece_locations[piece_as_index].pop();
-                },
-                None => {}
-            }
+    fn is_white_piece(piece: Piece) -> bool {
+        piece as u8 >= 7
     }
 
-    fn move_piece(&mut self, piece: Piece, index: usize, new_location: usize) {
-        let piece_as_index = piece as usize;
-        match self.piece_locations[piece_as_index]
-            .iter()
-            .position(|&r| r == index) {
-                Some(i) => {
-                    self.piece_locations[piece_as_index][i] = new_location;
-                },
-                None => {}
-            }
+    // all board writes go through here to keep the bitboards in sync
+    fn set_square(&mut self, index: usize, piece: Piece) {
+        let bit = square_bb(index);
+        let old_piece = self.board[index];
+        if old_piece != Piece::Empty {
+            self.bitboards[old_piece as usize] &= !bit;
+            self.color_occupancy[Engine::is_white_piece(old_piece) as usize] &= !bit;
+            self.occupancy &= !bit;
+        }
+
+        self.board[index] = piece;
+        if piece != Piece::Empty {
+            self.bitboards[piece as usize] |= bit;
+            self.color_occupancy[Engine::is_white_piece(piece) as usize] |= bit;
+            self.occupancy |= bit;
+        }
+    }
+
+    fn rebuild_bitboards(&mut self) {
+        self.bitboards = [EMPTY; 13];
+        self.color_occupancy = [EMPTY; 2];
+        self.occupancy = EMPTY;
+        for index in 0..64 {
+            let piece = self.board[index];
+            self.board[index] = Piece::Empty;
+            self.set_square(index, piece);
+        }
     }
 
     pub fn find_piece_in_file(&self, piece: usize, file: &str) -> i32 {
-        for location in self.piece_locations[piece].iter() {
-            let found_file = Engine::index_to_notation(*location).chars().nth(0).unwrap().to_string();
+        for location in squares(self.bitboards[piece]) {
+            let found_file = Engine::index_to_notation(location).chars().nth(0).unwrap().to_string();
             if found_file == file {
-                return *location as i32;
+                return location as i32;
             }
         }
 
@@ -217,10 +270,7 @@ impl Engine {
     }
 
     pub fn notation_to_index(rank: char, file: char) -> usize {
-        let mut y: usize = match rank.to_string().parse() {
-            Ok(n) => n,
-            Err(_e) => 0,
-        };
+        let mut y: usize = rank.to_string().parse().unwrap_or_default();
         y = 8 - y;
 
         // notation must be in ascii but that should always be the case
@@ -259,412 +309,359 @@ impl Engine {
             hash ^= self.zobrist_hash_table[65][0];
         }
 
-        // en passant
-        if self.en_passant_square != -1 {
-            hash ^= self.zobrist_hash_table[66][self.en_passant_square as usize];
-        }
-
-        hash
+        hash ^ self.hash_en_passant()
     }
 
-    // todo: add error handling
-    pub fn parse_fen(&mut self, fen: &str) {
-        self.board = [Piece::Empty; 64];
-        for i in 0..self.piece_locations.len() {
-            self.piece_locations[i].clear();
-        }
-
-        let fields: Vec<&str> = fen.split(" ").collect();
-        if fields.len() != 6 {
-            return; // invalid fen string
+    // the engine is left unchanged when the fen is rejected. the move counters are optional
+    pub fn parse_fen(&mut self, fen: &str) -> Result<(), FenError> {
+        let fields: Vec<&str> = fen.split_whitespace().collect();
+        if fields.len() != 4 && fields.len() != 6 {
+            return Err(FenError::FieldCount(fields.len()));
         }
 
         // parse pieces
-        let mut board_index: usize = 0;
-        let ranks = fields[0].split("/");
-        for r in ranks {
-            let terms = r.split("");
-            for t in terms {
-                if t == "" {
+        let ranks: Vec<&str> = fields[0].split('/').collect();
+        if ranks.len() != 8 {
+            return Err(FenError::RankCount(ranks.len()));
+        }
+        let mut board = [Piece::Empty; 64];
+        for (y, rank) in ranks.iter().enumerate() {
+            let mut x: usize = 0;
+            for c in rank.chars() {
+                if let Some(empty_squares) = c.to_digit(10) {
+                    x += empty_squares as usize;
                     continue;
                 }
-                let num: usize = match t.parse() {
-                    Ok(n) => {
-                        n
-                    },
-                    Err(_e) => {
-                        let piece = self.fen_to_piece_map
-                            .entry(String::from(t))
-                            .or_insert(Piece::Empty);
-                        self.board[board_index] = *piece;
-                        self.piece_locations[*piece as usize].push(board_index);
-                        1
-                    }
+
+                let piece = match self.fen_to_piece_map.get(c.to_string().as_str()) {
+                    Some(piece) => *piece,
+                    None => return Err(FenError::InvalidPiece(c))
                 };
-                board_index += num;
+                if x < 8 {
+                    board[y * 8 + x] = piece;
+                }
+                x += 1;
+            }
+            if x != 8 {
+                return Err(FenError::RankLength { rank: 8 - y, length: x });
             }
         }
 
-        // parse turn
-        self.white_turn = fields[1] == "w";
-
-        // parse castle flags
-        self.castle_status = CastleStatus::UNSET;
-        if fields[2].contains('K') {
-            self.castle_status |= CastleStatus::WHITE_KING;
-        }
-        if fields[2].contains('Q') {
-            self.castle_status |= CastleStatus::WHITE_QUEEN;
+        for (king, white) in [(Piece::King_W, true), (Piece::King_B, false)] {
+            match board.iter().filter(|p| **p == king).count() {
+                0 => return Err(FenError::MissingKing { white }),
+                1 => {},
+                _ => return Err(FenError::TooManyKings { white })
+            }
         }
-        if fields[2].contains('k') {
-            self.castle_status |= CastleStatus::BLACK_KING;
+        for index in (0..8).chain(56..64) {
+            if board[index] == Piece::Pawn_W || board[index] == Piece::Pawn_B {
+                return Err(FenError::PawnOnBackRank(Engine::index_to_notation(index)));
+            }
         }
-        if fields[2].contains('q') {
-            self.castle_status |= CastleStatus::BLACK_QUEEN;
+
+        // parse turn
+        let white_turn = match fields[1] {
+            "w" => true,
+            "b" => false,
+            side => return Err(FenError::InvalidSideToMove(String::from(side)))
+        };
+
+        // parse castle flags, each right needs its king and rook on their starting squares
+        let castle_rights = [
+            ('K', CastleStatus::WHITE_KING, 60, Piece::King_W, 63, Piece::Rook_W),
+            ('Q', CastleStatus::WHITE_QUEEN, 60, Piece::King_W, 56, Piece::Rook_W),
+            ('k', CastleStatus::BLACK_KING, 4, Piece::King_B, 7, Piece::Rook_B),
+            ('q', CastleStatus::BLACK_QUEEN, 4, Piece::King_B, 0, Piece::Rook_B)
+        ];
+        let mut castle_status = CastleStatus::UNSET;
+        if fields[2] != "-" {
+            for c in fields[2].chars() {
+                let (_, flag, king_index, king, rook_index, rook) = match castle_rights.iter().find(|r| r.0 == c) {
+                    Some(right) => *right,
+                    None => return Err(FenError::InvalidCastling(String::from(fields[2])))
+                };
+                if castle_status.contains(flag) {
+                    return Err(FenError::InvalidCastling(String::from(fields[2])));
+                }
+                if board[king_index] != king || board[rook_index] != rook {
+                    return Err(FenError::CastlingRightsMismatch(c));
+                }
+                castle_status |= flag;
+            }
         }
 
-        // parse en passant square
+        // parse en passant square, the pawn that just moved two squares must be in front of it
+        let mut en_passant_square = -1;
         if fields[3] != "-" {
-            self.en_passant_square = Engine::notation_to_index(
-                fields[3].chars().nth(1).unwrap(),
-                fields[3].chars().nth(0).unwrap()
-            ) as i32;
+            let chars: Vec<char> = fields[3].chars().collect();
+            if chars.len() != 2 || !('a'..='h').contains(&chars[0]) || !('1'..='8').contains(&chars[1]) {
+                return Err(FenError::InvalidEnPassant(String::from(fields[3])));
+            }
+
+            let index = Engine::notation_to_index(chars[1], chars[0]);
+            let (expected_row, pawn_index, origin_index, pawn) = if white_turn {
+                (2, index + 8, index.wrapping_sub(8), Piece::Pawn_B)
+            } else {
+                (5, index.wrapping_sub(8), index + 8, Piece::Pawn_W)
+            };
+            if index / 8 != expected_row || board[pawn_index] != pawn || board[index] != Piece::Empty || board[origin_index] != Piece::Empty {
+                return Err(FenError::ImpossibleEnPassant(String::from(fields[3])));
+            }
+            en_passant_square = index as i32;
         }
 
         // parse move counts
-        self.move_rep_count = fields[4].parse().unwrap();
-        self.move_count = fields[5].parse().unwrap();
-        self.move_count = self.move_count * 2 - 2;
+        let (mut move_rep_count, mut full_moves) = (0, 1);
+        if fields.len() == 6 {
+            move_rep_count = match fields[4].parse::<u32>() {
+                Ok(n) => n as i32,
+                Err(_e) => return Err(FenError::InvalidMoveCounter(String::from(fields[4])))
+            };
+            full_moves = match fields[5].parse::<u32>() {
+                Ok(n) if n > 0 => n as i32,
+                _ => return Err(FenError::InvalidMoveCounter(String::from(fields[5])))
+            };
+        }
+
+        let previous_board = self.board;
+        self.board = board;
+        self.rebuild_bitboards();
+        if self.is_in_check(!white_turn) {
+            self.board = previous_board;
+            self.rebuild_bitboards();
+            return Err(FenError::OpponentInCheck);
+        }
+
+        self.white_turn = white_turn;
+        self.castle_status = castle_status;
+        self.en_passant_square = en_passant_square;
+        self.move_rep_count = move_rep_count;
+        self.move_count = (full_moves - 1) * 2 + !white_turn as i32;
 
         self.board_hash = self.hash_board();
         self.repetition_history.clear();
         self.repetition_history.push(self.board_hash);
-        self.all_valid_moves = self.get_all_valid_moves(false, &mut vec![]);
-    }
-
-    pub fn use_historical_board(&mut self) {
-        self.board_deltas.clear();
-        self.saved_evaluations.clear();
-        self.board_hash = self.hash_board();
-        self.best_move = Default::default();
-        self.all_valid_moves = self.get_all_valid_moves(
-            false, 
-            &mut vec![]
-        );
-    }
-
-    pub fn finish_turn(&mut self) {
-        self.white_turn = !self.white_turn;
-        self.board_hash = self.hash_board();
-        self.board_deltas.clear();
-        self.all_valid_moves = self.get_all_valid_moves(false, &mut vec![]);
+        self.all_valid_moves = self.get_all_valid_moves(false, self.get_attacked_squares(self.white_turn));
         self.in_check = self.is_in_check(self.white_turn);
-        self.saved_evaluations.clear();
+        self.game = Game::new(&self.to_fen());
 
-        self.move_count += 1;
-        self.move_rep_count += 1;
+        // a new position starts a new game on fresh clocks
+        self.chess_clock = self.chess_clock.as_ref().map(|c| ChessClock::new(c.control()));
+        self.turn_start_time = self.clock.now();
+
+        Ok(())
     }
 
-    pub fn force_make_move(&mut self, from_index: usize, move_info: &MoveInfo, finish_turn: bool) {
-        let to_index = move_info.index;
-        let moving_piece = self.board[from_index];
-        let captured_piece = self.board[to_index];
-
-        self.board_deltas.push(BoardDelta {
-            index: to_index as i32,
-            piece: captured_piece,
-            target: -1
-        });
-        self.board_deltas.push(BoardDelta {
-            index: from_index as i32,
-            piece: moving_piece,
-            target: to_index as i32
-        });
-        self.board[to_index] = moving_piece;
-        self.board[from_index] = Piece::Empty;
-
-        // promotion check
-        let mut promoted = false;
-        let y: usize = (to_index as f32 * 0.125) as usize; // 0.125 = 1/8
-        if moving_piece == Piece::Pawn_W && y == 0 {
-            self.board[to_index] = move_info.data;
-            self.remove_piece(Piece::Pawn_W, from_index);
-            self.piece_locations[move_info.data as usize].push(to_index);
-            self.board_deltas.push(BoardDelta {
-                index: -1,
-                piece: move_info.data,
-                target: to_index as i32
-            });
-            promoted = true;
-        }
-        else if moving_piece == Piece::Pawn_B && y == 7 {
-            self.board[to_index] = move_info.data;
-            self.remove_piece(Piece::Pawn_B, from_index);
-            self.piece_locations[move_info.data as usize].push(to_index);
-            self.board_deltas.push(BoardDelta {
-                index: -1,
-                piece: move_info.data,
-                target: to_index as i32
-            });
-            promoted = true;
+    pub fn to_fen(&self) -> String {
+        let mut fen = String::new();
+        for y in 0..8 {
+            let mut empty_squares = 0;
+            for x in 0..8 {
+                let piece = self.board[y * 8 + x];
+                if piece == Piece::Empty {
+                    empty_squares += 1;
+                    continue;
+                }
+
+                if empty_squares > 0 {
+                    fen += &empty_squares.to_string();
+                    empty_squares = 0;
+                }
+                fen.push(" kqrbnpKQRBNP".as_bytes()[piece as usize] as char);
+            }
+            if empty_squares > 0 {
+                fen += &empty_squares.to_string();
+            }
+            if y < 7 {
+                fen.push('/');
+            }
         }
 
-        // en passant check
-        if to_index as i32 == self.en_passant_square { // capturing en passant, so remove the pawn and add a delta
-            if moving_piece == Piece::Pawn_W {
-                self.board_deltas.push(BoardDelta {
-                    index: to_index as i32 + 8,
-                    piece: Piece::Pawn_B,
-                    target: -1
-                });
-                self.board[to_index + 8] = Piece::Empty;
-                self.remove_piece(Piece::Pawn_B, to_index + 8);
-            } else if moving_piece == Piece::Pawn_B {
-                self.board_deltas.push(BoardDelta {
-                    index: to_index as i32 - 8,
-                    piece: Piece::Pawn_W,
-                    target: -1
-                });
-                self.board[to_index - 8] = Piece::Empty;
-                self.remove_piece(Piece::Pawn_W, to_index - 8);
+        fen += if self.white_turn { " w " } else { " b " };
+
+        let castle_chars = [
+            (CastleStatus::WHITE_KING, 'K'),
+            (CastleStatus::WHITE_QUEEN, 'Q'),
+            (CastleStatus::BLACK_KING, 'k'),
+            (CastleStatus::BLACK_QUEEN, 'q')
+        ];
+        if self.castle_status.is_empty() {
+            fen.push('-');
+        }
+        for (flag, c) in castle_chars.iter() {
+            if self.castle_status.contains(*flag) {
+                fen.push(*c);
             }
         }
 
-        if moving_piece == Piece::Pawn_W && from_index - to_index == 16 { // moving two spaces up
-            self.en_passant_square = from_index as i32 - 8;
-        } else if moving_piece == Piece::Pawn_B && to_index - from_index == 16 { // moving two spaces down
-            self.en_passant_square = from_index as i32 + 8;
+        if self.en_passant_square == -1 {
+            fen += " -";
         } else {
-            self.en_passant_square = -1;
+            fen += &format!(" {}", Engine::index_to_notation(self.en_passant_square as usize));
         }
 
-        // update moved piece position unless promoted since that is already handled
-        if !promoted {
-            self.move_piece(moving_piece, from_index, to_index);
-        }
+        fen + &format!(" {} {}", self.move_rep_count, self.move_count / 2 + 1)
+    }
 
-        // remove captured piece
-        if captured_piece != Piece::Empty {
-            self.remove_piece(captured_piece, to_index);
-        }
+    // the history can't be recovered from a snapshot, so the game restarts from it
+    pub fn use_historical_board(&mut self) {
+        self.board_hash = self.hash_board();
+        self.game = Game::new(&self.to_fen());
+        self.best_move = Default::default();
+        self.all_valid_moves = self.get_all_valid_moves(
+            false,
+            self.get_attacked_squares(self.white_turn)
+        );
+    }
+
+    // rebuilds what play_game_move tracks after moving through the game history
+    fn finish_history_step(&mut self) {
+        let last_move = self.game.played_moves().last().copied().unwrap_or_default();
+        self.castled_this_turn = last_move.is_castle();
+        self.piece_captured_this_turn = last_move.is_capture();
+        self.best_move = Default::default();
+        self.last_move_san = match self.game.pop() {
+            Some(undo) => {
+                self.unmake_move(undo);
+                let san = self.move_to_san(last_move);
+                let undo = self.make_move(last_move);
+                self.game.push(last_move, undo);
+                san
+            }
+            None => String::new()
+        };
 
-        if finish_turn {
-            self.finish_turn();
+        // positions after each ply, recorded the same way play_game_move does
+        let undos = self.game.undos();
+        let start_hash = undos.first().map_or(self.board_hash, |u| u.board_hash);
+        let mut positions: Vec<(u64, i32)> = undos.iter().skip(1).map(|u| (u.board_hash, u.move_rep_count)).collect();
+        if !undos.is_empty() {
+            positions.push((self.board_hash, self.move_rep_count));
+        }
 
-            // update board repetition history
-            if moving_piece == Piece::Pawn_W || moving_piece == Piece::Pawn_B || captured_piece != Piece::Empty { // repetitions not possible with these moves
+        self.repetition_history = vec![start_hash];
+        for (hash, move_rep_count) in positions {
+            if move_rep_count == 0 {
                 self.repetition_history.clear();
-                self.move_rep_count = 0;
-            } else {
-                self.repetition_history.push(self.board_hash);
             }
+            self.repetition_history.push(hash);
         }
+
+        self.finish_turn();
     }
 
-    // cannot be pub since wasm_bindgen doesnt support struct slice refs
-    fn unmake_move(&mut self, deltas: &[BoardDelta], starting_en_passant: i32) {
-        self.white_turn = !self.white_turn;
+    // refreshes the state the frontend reads after a move was played on the game board
+    pub fn finish_turn(&mut self) {
+        self.all_valid_moves = self.get_all_valid_moves(false, self.get_attacked_squares(self.white_turn));
+        self.in_check = self.is_in_check(self.white_turn);
+    }
 
-        for elem in deltas.iter() {
-            let target_as_usize = elem.target as usize;
-            let piece_as_usize = elem.piece as usize;
-            let index_as_usize = elem.index as usize;
-
-            if elem.piece != Piece::Empty { // ignore any empty piece entries
-                if elem.index == -1 { // if the original index is -1, it means the piece was created from promotion, so remove the piece
-                    self.remove_piece(elem.piece, target_as_usize)
-                } else if self.board[index_as_usize] != Piece::Empty || (elem.piece == Piece::Pawn_B && elem.index - 8 == starting_en_passant) || (elem.piece == Piece::Pawn_W && elem.index + 8 == starting_en_passant) { // was captured or en passant so add the piece back to register
-                    self.piece_locations[piece_as_usize].push(index_as_usize);
-                } else if elem.target != -1 { // otherwise just move it back
-                    match self.piece_locations[piece_as_usize]
-                        .iter()
-                        .position(|&r| r == target_as_usize) {
-                            Some(i) => {
-                                self.piece_locations[piece_as_usize][i] = index_as_usize;
-                            },
-                            None => {
-                                self.piece_locations[piece_as_usize].push(index_as_usize);
-                            }
-                        };
-                }
-            }
+    fn hash_piece(&self, index: usize, piece: Piece) -> u64 {
+        if piece == Piece::Empty {
+            return 0;
+        }
+        self.zobrist_hash_table[index][piece as usize - 1]
+    }
 
-            if elem.index != -1 {
-                self.board[index_as_usize] = elem.piece;
+    fn hash_castle_status(&self, castle_status: CastleStatus) -> u64 {
+        let mut hash = 0;
+        for (i, flag) in [CastleStatus::WHITE_KING, CastleStatus::WHITE_QUEEN, CastleStatus::BLACK_KING, CastleStatus::BLACK_QUEEN].iter().enumerate() {
+            if castle_status.contains(*flag) {
+                hash ^= self.zobrist_hash_table[64][i];
             }
         }
+        hash
     }
 
-    pub fn update_castle_status(&mut self, from_index: usize, to_index: usize) -> bool {
-        let moving_piece = self.board[from_index];
-        let mut castled = false;
-
-        if moving_piece == Piece::King_W {
-            if !(self.castle_status & CastleStatus::WHITE_KING).is_empty() && to_index == 62 {
-                self.board_deltas.push(BoardDelta {
-                    index: 63,
-                    piece: self.board[63],
-                    target: 61
-                });
-                self.board_deltas.push(BoardDelta {
-                    index: 61,
-                    piece: self.board[61],
-                    target: -1
-                });
-                self.move_piece(Piece::Rook_W, 63, 61);
-                self.board[63] = Piece::Empty;
-                self.board[61] = Piece::Rook_W;
-                castled = true;
-            } else if !(self.castle_status & CastleStatus::WHITE_QUEEN).is_empty() && to_index == 58 {
-                self.board_deltas.push(BoardDelta {
-                    index: 56,
-                    piece: self.board[56],
-                    target: 59
-                });
-                self.board_deltas.push(BoardDelta {
-                    index: 59,
-                    piece: self.board[59],
-                    target: -1
-                });
-                self.move_piece(Piece::Rook_W, 56, 59);
-                self.board[56] = Piece::Empty;
-                self.board[59] = Piece::Rook_W;
-                castled = true;
-            }
+    // only hashed when a pawn of the side to move could capture, otherwise the position repeats one without the square
+    fn hash_en_passant(&self) -> u64 {
+        if self.en_passant_square == -1 {
+            return 0;
+        }
+        let square = self.en_passant_square as usize;
+        let pawns = self.bitboards[if self.white_turn { Piece::Pawn_W } else { Piece::Pawn_B } as usize];
+        if self.attack_tables.pawn(!self.white_turn, square) & pawns == EMPTY {
+            return 0;
+        }
+        self.zobrist_hash_table[66][square]
+    }
 
-            self.castle_status &= !CastleStatus::WHITE_KING;
-            self.castle_status &= !CastleStatus::WHITE_QUEEN;
-        } else if moving_piece == Piece::King_B {
-            if !(self.castle_status & CastleStatus::BLACK_KING).is_empty() && to_index == 6 {
-                self.board_deltas.push(BoardDelta {
-                    index: 7,
-                    piece: self.board[7],
-                    target: 5
-                });
-                self.board_deltas.push(BoardDelta {
-                    index: 5,
-                    piece: self.board[5],
-                    target: -1
-                });
-                self.move_piece(Piece::Rook_B, 7, 5);
-                self.board[7] = Piece::Empty;
-                self.board[5] = Piece::Rook_B;
-                castled = true;
-            } else if !(self.castle_status & CastleStatus::BLACK_QUEEN).is_empty() && to_index == 2 {
-                self.board_deltas.push(BoardDelta {
-                    index: 0,
-                    piece: self.board[0],
-                    target: 3
-                });
-                self.board_deltas.push(BoardDelta {
-                    index: 3,
-                    piece: self.board[3],
-                    target: -1
-                });
-                self.move_piece(Piece::Rook_B, 0, 3);
-                self.board[0] = Piece::Empty;
-                self.board[3] = Piece::Rook_B;
-                castled = true;
-            }
+    // moves a piece between two squares while keeping the hash up to date
+    fn move_piece(&mut self, from_index: usize, to_index: usize) {
+        let piece = self.board[from_index];
+        self.board_hash ^= self.hash_piece(from_index, piece) ^ self.hash_piece(to_index, piece);
+        self.set_square(from_index, Piece::Empty);
+        self.set_square(to_index, piece);
+    }
 
-            self.castle_status &= !CastleStatus::BLACK_KING;
-            self.castle_status &= !CastleStatus::BLACK_QUEEN;
-        } else if moving_piece == Piece::Rook_W && from_index == 56 {
-            self.castle_status &= !CastleStatus::WHITE_QUEEN;
-        } else if moving_piece == Piece::Rook_W && from_index == 63 {
-            self.castle_status &= !CastleStatus::WHITE_KING;
-        } else if moving_piece == Piece::Rook_B && from_index == 0 {
-            self.castle_status &= !CastleStatus::BLACK_QUEEN;
-        } else if moving_piece == Piece::Rook_B && from_index == 7 {
-            self.castle_status &= !CastleStatus::BLACK_KING;
-        }
-
-        castled
-    }
-
-    // cannot be pub since wasm_bindgen doesnt support struct slice refs
-    fn update_hash(&self, deltas: &[BoardDelta], current_hash: u64, old_en_passant: i32, old_castle_status: CastleStatus) -> u64 {
-        let mut new_hash = current_hash;
-
-        // positions
-        for elem in deltas.iter() {
-            if elem.index != -1 { // -1 entries are usually for tracking, so don't worry about them when updating the hash
-                let position = elem.index as usize;
-                let piece = elem.piece as i32 - 1;
-                let new_piece = self.board[position] as i32 - 1;
-                if piece >= 0 {
-                    new_hash ^= self.zobrist_hash_table[position][piece as usize];
-                }
-                if new_piece >= 0 {
-                    new_hash ^= self.zobrist_hash_table[position][new_piece as usize];
-                }
-            }
+    fn castle_rook_squares(mov: Move) -> (usize, usize) {
+        match (mov.from(), mov.flags()) {
+            (60, MoveFlag::KING_CASTLE) => (63, 61),
+            (60, _) => (56, 59),
+            (_, MoveFlag::KING_CASTLE) => (7, 5),
+            (_, _) => (0, 3)
         }
+    }
 
-        // castling (flip)
-        if !(old_castle_status & CastleStatus::WHITE_KING).is_empty() != !(self.castle_status & CastleStatus::WHITE_KING).is_empty() {
-            new_hash ^= self.zobrist_hash_table[64][0];
-        }
-        if !(old_castle_status & CastleStatus::WHITE_QUEEN).is_empty() != !(self.castle_status & CastleStatus::WHITE_QUEEN).is_empty() {
-            new_hash ^= self.zobrist_hash_table[64][1];
-        }
-        if !(old_castle_status & CastleStatus::BLACK_KING).is_empty() != !(self.castle_status & CastleStatus::BLACK_KING).is_empty() {
-            new_hash ^= self.zobrist_hash_table[64][2];
+    // rights lost when a piece moves from or to a square, which covers rooks being captured
+    fn castle_rights_lost(index: usize) -> CastleStatus {
+        match index {
+            60 => CastleStatus::WHITE_KING | CastleStatus::WHITE_QUEEN,
+            63 => CastleStatus::WHITE_KING,
+            56 => CastleStatus::WHITE_QUEEN,
+            4 => CastleStatus::BLACK_KING | CastleStatus::BLACK_QUEEN,
+            7 => CastleStatus::BLACK_KING,
+            0 => CastleStatus::BLACK_QUEEN,
+            _ => CastleStatus::UNSET
         }
-        if !(old_castle_status & CastleStatus::BLACK_QUEEN).is_empty() != !(self.castle_status & CastleStatus::BLACK_QUEEN).is_empty() {
-            new_hash ^= self.zobrist_hash_table[64][3];
+    }
+
+    // plays a move on the game board (rather than in the search tree) and updates the game history
+    fn play_game_move(&mut self, mov: Move) {
+        let now = self.clock.now();
+        if let Some(chess_clock) = self.chess_clock.as_mut() {
+            chess_clock.record_move(self.white_turn, now - self.turn_start_time);
         }
+        self.turn_start_time = now;
 
-        // turn (flip)
-        new_hash ^= self.zobrist_hash_table[65][0];
+        self.last_move_san = self.move_to_san(mov);
+        self.castled_this_turn = mov.is_castle();
+        self.piece_captured_this_turn = mov.is_capture();
+        let undo = self.make_move(mov);
+        self.game.push(mov, undo);
+        self.finish_turn();
 
-        // en passant
-        if old_en_passant != self.en_passant_square {
-            if old_en_passant != -1 {
-                new_hash ^= self.zobrist_hash_table[66][old_en_passant as usize];
-            }
-            if self.en_passant_square != -1 {
-                new_hash ^= self.zobrist_hash_table[66][self.en_passant_square as usize];
-            }
+        if self.move_rep_count == 0 { // earlier positions cannot repeat after pawn moves or captures, this one still can
+            self.repetition_history.clear();
         }
-
-        new_hash
+        self.repetition_history.push(self.board_hash);
     }
 
     pub fn piece_count(&self) -> i32 {
-        let mut count = 0;
-        for i in 1..self.piece_locations.len() {
-            count += self.piece_locations[i].len();
-        }
-        count as i32
+        self.occupancy.count_ones() as i32
     }
 
+    // true once the game is drawn or a draw can be claimed, which the app always claims
     pub fn check_for_draw(&self) -> bool {
-        if self.move_rep_count >= 50 {
-            log("draw by 50 move rep");
-            return true;
-        }
+        self.game_status().is_draw()
+    }
 
-        if !self.white_turn { // white's last move cannot be a draw
-            return false;
-        }
+    pub fn is_checkmate(&self) -> bool {
+        matches!(self.game_status(), GameStatus::Checkmate(_))
+    }
 
-        if self.piece_count() == 2 { // only the kings are left
-            return true;
-        }
+    pub fn is_claimable_draw(&self) -> bool {
+        self.game_status().is_claimable_draw()
+    }
 
-        let mut count = 0;
-        for elem in self.repetition_history.iter() {
-            if *elem == self.board_hash {
-                count += 1;
-            }
-            if count == 3 {
-                log("draw by threefold rep");
-                return true; // threefold rep
-            }
-        }
+    // the reason the game ended, or "Ongoing"
+    pub fn game_status_text(&self) -> String {
+        self.game_status().to_string()
+    }
 
-        false
+    pub fn game_result(&self) -> String {
+        String::from(self.game_status().result())
     }
 
     pub fn get_piece_value(piece: Piece) -> i32 {
@@ -696,7 +693,7 @@ impl Engine {
         let start_index = if white { 8 } else { 2 };
         let end_index = if white {12 } else { 6 };
         for i in start_index..=end_index {
-            value += Engine::get_piece_value(Piece::from_num(i)) * self.piece_locations[i as usize].len() as i32; // convert index to a piece (should always be defined and safe here)
+            value += Engine::get_piece_value(Piece::from_num(i)) * self.bitboards[i as usize].count_ones() as i32; // convert index to a piece (should always be defined and safe here)
         }
 
         value
@@ -715,9 +712,9 @@ impl Engine {
         }
 
         let mut value = 0;
-        for pos in self.piece_locations[piece as usize].iter() {
+        for pos in squares(self.bitboards[piece as usize]) {
             value += Engine::read_square_table_value(
-                *pos,
+                pos,
                 table,
                 white
             );
@@ -770,8 +767,8 @@ impl Engine {
 
         let white_material = self.count_material(true);
         let black_material = self.count_material(false);
-        let white_material_no_pawns = white_material - self.piece_locations[Piece::Pawn_W as usize].len() as i32 * Engine::get_piece_value(Piece::Pawn_W);
-        let black_material_no_pawns = black_material - self.piece_locations[Piece::Pawn_B as usize].len() as i32 * Engine::get_piece_value(Piece::Pawn_B);
+        let white_material_no_pawns = white_material - self.bitboards[Piece::Pawn_W as usize].count_ones() as i32 * Engine::get_piece_value(Piece::Pawn_W);
+        let black_material_no_pawns = black_material - self.bitboards[Piece::Pawn_B as usize].count_ones() as i32 * Engine::get_piece_value(Piece::Pawn_B);
 
         let endgame_material_threshold = (Value::ROOK * 2) + Value::BISHOP + Value::KNIGHT;
         let white_end_game_weight = 1.0 - f32::min(1.0, white_material_no_pawns as f32 / endgame_material_threshold as f32);
@@ -783,10 +780,12 @@ impl Engine {
         white_eval += self.evaluate_square_tables(true, white_end_game_weight) * development_weight;
         black_eval += self.evaluate_square_tables(false, white_end_game_weight) * development_weight;
 
-        let white_x = (self.piece_locations[Piece::King_W as usize][0] % 8) as i32;
-        let white_y = (self.piece_locations[Piece::King_W as usize][0] as f32 * 0.125) as i32;
-        let black_x = (self.piece_locations[Piece::King_B as usize][0] % 8) as i32;
-        let black_y = (self.piece_locations[Piece::King_B as usize][0] as f32 * 0.125) as i32;
+        let white_king = lsb(self.bitboards[Piece::King_W as usize]);
+        let black_king = lsb(self.bitboards[Piece::King_B as usize]);
+        let white_x = (white_king % 8) as i32;
+        let white_y = (white_king / 8) as i32;
+        let black_x = (black_king % 8) as i32;
+        let black_y = (black_king / 8) as i32;
         let distance_between = i32::abs(white_x - black_x) + i32::abs(white_y - black_y);
         white_eval += Engine::evaluate_end_game_position(white_end_game_weight, black_x, black_y, distance_between);
         black_eval += Engine::evaluate_end_game_position(black_end_game_weight, white_x, white_y, distance_between);
@@ -799,613 +798,241 @@ impl Engine {
         evaluation
     }
 
-    fn trace_valid_squares(&mut self, index: usize, slope_x: i32, slope_y: i32, white: bool, empty_only: bool, update_pins: bool, x: i32, y: i32, in_array: &mut Vec<usize>) {
-        let xy_max = 7;
-        let len = self.board.len();
-
-        let mut x = x;
-        let mut y = y;
-        let mut current_index = index;
-        let mut obstructed = false;
-        let mut obstructed_index = 0;
-        while current_index < len {
-            if current_index != index {
-                if !obstructed {
-                    if empty_only {
-                        if self.board[current_index] == Piece::Empty {
-                            in_array.push(current_index);
-                        } else { break; }
-                    } else if self.board[current_index] == Piece::Empty ||
-                              (white && (self.board[current_index] as u8) < 7) ||
-                              (!white && (self.board[current_index] as u8) >= 7) {
-                        in_array.push(current_index);
-                    }
-                    obstructed = self.board[current_index] != Piece::Empty;
-                    obstructed_index = current_index;
-                } else if update_pins {
-                    // if we are tracing a white piece, look for a black piece blocking the way of the black king
-                    if self.board[current_index] == Piece::King_W ||
-                       self.board[current_index] == Piece::King_B ||
-                       self.board[current_index] == Piece::Empty {
-                        if white && self.board[current_index] == Piece::King_B && (self.board[current_index] as u8) < 7 {
-                            self.pinned_pieces.push(obstructed_index);
-                            break;
-                        } else if !white && self.board[current_index] == Piece::King_W && (self.board[current_index] as u8) >= 7 {
-                            self.pinned_pieces.push(obstructed_index);
-                            break;
-                        }
-                    } else { break; }
-                } else { break; }
-            }
-
-            if slope_x == -1 && x == 0 { break; }
-            if slope_x == 1 && x == xy_max { break; }
-            if slope_y == -1 && y == 0 { break; }
-            if slope_y == 1 && y == xy_max { break; }
-
-            x += slope_x;
-            y += slope_y;
-            current_index += (slope_x + (slope_y * 8)) as usize;
+    // squares a piece attacks regardless of what occupies them
+    fn get_attacks(&self, index: usize, piece: Piece, occupancy: Bitboard) -> Bitboard {
+        match piece {
+            Piece::Rook_W | Piece::Rook_B => self.attack_tables.rook(index, occupancy),
+            Piece::Bishop_W | Piece::Bishop_B => self.attack_tables.bishop(index, occupancy),
+            Piece::Queen_W | Piece::Queen_B => self.attack_tables.queen(index, occupancy),
+            Piece::Knight_W | Piece::Knight_B => self.attack_tables.knight(index),
+            Piece::King_W | Piece::King_B => self.attack_tables.king(index),
+            Piece::Pawn_W => self.attack_tables.pawn(true, index),
+            Piece::Pawn_B => self.attack_tables.pawn(false, index),
+            _ => EMPTY
         }
     }
 
-    #[allow(unused_assignments)]
-    fn get_valid_squares(&mut self, index: usize, piece: Piece, attack_only: bool, update_pins: bool, in_array: &mut Vec<usize>) {
-        let x = index as i32 % 8;
-        let y = (index as f32 * 0.125) as i32; // 0.125 = 1/8
-        let xy_max: i32 = 7;
-        let is_white = piece as u8 >= 7;
+    fn get_valid_squares(&self, index: usize, piece: Piece, attack_only: bool) -> Bitboard {
+        let is_white = Engine::is_white_piece(piece);
+        let own = self.color_occupancy[is_white as usize];
+        let enemy = self.color_occupancy[!is_white as usize];
+        let attacks = self.get_attacks(index, piece, self.occupancy);
 
         match piece {
-            Piece::Rook_W | Piece::Rook_B => {
-                self.trace_valid_squares( // right
-                    index,
-                    1,
-                    0,
-                    is_white,
-                    false,
-                    update_pins,
-                    x,
-                    y,
-                    in_array
-                );
-                self.trace_valid_squares( // left
-                    index,
-                    -1,
-                    0,
-                    is_white,
-                    false,
-                    update_pins,
-                    x,
-                    y,
-                    in_array
-                );
-                self.trace_valid_squares( // down
-                    index,
-                    0,
-                    1,
-                    is_white,
-                    false,
-                    update_pins,
-                    x,
-                    y,
-                    in_array
-                );
-                self.trace_valid_squares( // up
-                    index,
-                    0,
-                    -1,
-                    is_white,
-                    false,
-                    update_pins,
-                    x,
-                    y,
-                    in_array
-                );
-            },
-            Piece::Queen_W | Piece::Queen_B => {
-                self.trace_valid_squares( // right
-                    index,
-                    1,
-                    0,
-                    is_white,
-                    false,
-                    update_pins,
-                    x,
-                    y,
-                    in_array
-                );
-                self.trace_valid_squares( // left
-                    index,
-                    -1,
-                    0,
-                    is_white,
-                    false,
-                    update_pins,
-                    x,
-                    y,
-                    in_array
-                );
-                self.trace_valid_squares( // down
-                    index,
-                    0,
-                    1,
-                    is_white,
-                    false,
-                    update_pins,
-                    x,
-                    y,
-                    in_array
-                );
-                self.trace_valid_squares( // up
-                    index,
-                    0,
-                    -1,
-                    is_white,
-                    false,
-                    update_pins,
-                    x,
-                    y,
-                    in_array
-                );
-                self.trace_valid_squares( // up right
-                    index,
-                    1,
-                    -1,
-                    is_white,
-                    false,
-                    update_pins,
-                    x,
-                    y,
-                    in_array
-                );
-                self.trace_valid_squares( // up left
-                    index,
-                    -1,
-                    -1,
-                    is_white,
-                    false,
-                    update_pins,
-                    x,
-                    y,
-                    in_array
-                );
-                self.trace_valid_squares( // down right
-                    index,
-                    1,
-                    1,
-                    is_white,
-                    false,
-                    update_pins,
-                    x,
-                    y,
-                    in_array
-                );
-                self.trace_valid_squares( // down left
-                    index,
-                    -1,
-                    1,
-                    is_white,
-                    false,
-                    update_pins,
-                    x,
-                    y,
-                    in_array
-                );
-            },
-            Piece::Bishop_W | Piece::Bishop_B => {
-                self.trace_valid_squares( // up right
-                    index,
-                    1,
-                    -1,
-                    is_white,
-                    false,
-                    update_pins,
-                    x,
-                    y,
-                    in_array
-                );
-                self.trace_valid_squares( // up left
-                    index,
-                    -1,
-                    -1,
-                    is_white,
-                    false,
-                    update_pins,
-                    x,
-                    y,
-                    in_array
-                );
-                self.trace_valid_squares( // down right
-                    index,
-                    1,
-                    1,
-                    is_white,
-                    false,
-                    update_pins,
-                    x,
-                    y,
-                    in_array
-                );
-                self.trace_valid_squares( // down left
-                    index,
-                    -1,
-                    1,
-                    is_white,
-                    false,
-                    update_pins,
-                    x,
-                    y,
-                    in_array
-                );
-            },
             Piece::Pawn_W | Piece::Pawn_B => {
-                let mut to: usize = 0;
-                let min = if is_white { 1 } else { 7 };
-                let max = if is_white { 6 } else { 12 };
-                let x_min = x >= 1;
-                let x_max = x < xy_max;
-                let offset = 8;
-                let start_y = if is_white { 6 } else { 1 };
-
-                if is_white { to = index - offset; } else { to = index + offset; }          if !attack_only && (self.board[to] == Piece::Empty) { in_array.push(to); }
-                if is_white { to = index - offset * 2; } else { to = index + offset * 2; }  if !attack_only && y == start_y && (self.board[to] == Piece::Empty && self.board[if is_white { to + offset } else { to - offset }] == Piece::Empty) { in_array.push(to); }
-                if is_white { to = index - offset + 1; } else { to = index + offset + 1; }  if x_max && (((attack_only || self.board[to] != Piece::Empty) && (self.board[to] == Piece::Empty || ((self.board[to] as u8) >= min && (self.board[to] as u8) <= max))) || to as i32 == self.en_passant_square) { in_array.push(to); }
-                if is_white { to = index - offset - 1; } else { to = index + offset - 1; }  if x_min && (((attack_only || self.board[to] != Piece::Empty) && (self.board[to] == Piece::Empty || ((self.board[to] as u8) >= min && (self.board[to] as u8) <= max))) || to as i32 == self.en_passant_square) { in_array.push(to); }
-            },
-            Piece::King_W | Piece::King_B => {
-                let mut to: usize = 0;
-                let min = if is_white { 1 } else { 7 };
-                let max = if is_white { 6 } else { 12 };
-                let x_min = x >= 1;
-                let x_max = x < xy_max;
-                let y_min = y >= 1;
-                let y_max = y < xy_max;
-
-                to = index - 9; if x_min && y_min && (self.board[to] == Piece::Empty || ((self.board[to] as u8) >= min && (self.board[to] as u8) <= max)) { in_array.push(to); }
-                to = index - 8; if y_min &&          (self.board[to] == Piece::Empty || ((self.board[to] as u8) >= min && (self.board[to] as u8) <= max)) { in_array.push(to); }
-                to = index - 7; if x_max && y_min && (self.board[to] == Piece::Empty || ((self.board[to] as u8) >= min && (self.board[to] as u8) <= max)) { in_array.push(to); }
-                to = index - 1; if x_min &&          (self.board[to] == Piece::Empty || ((self.board[to] as u8) >= min && (self.board[to] as u8) <= max)) { in_array.push(to); }
-                to = index + 1; if x_max &&          (self.board[to] == Piece::Empty || ((self.board[to] as u8) >= min && (self.board[to] as u8) <= max)) { in_array.push(to); }
-                to = index + 7; if x_min && y_max && (self.board[to] == Piece::Empty || ((self.board[to] as u8) >= min && (self.board[to] as u8) <= max)) { in_array.push(to); }
-                to = index + 8; if y_max &&          (self.board[to] == Piece::Empty || ((self.board[to] as u8) >= min && (self.board[to] as u8) <= max)) { in_array.push(to); }
-                to = index + 9; if x_max && y_max && (self.board[to] == Piece::Empty || ((self.board[to] as u8) >= min && (self.board[to] as u8) <= max)) { in_array.push(to); }
-            },
-            Piece::Knight_W | Piece::Knight_B => {
-                let mut to: usize = 0;
-                let min = if is_white { 1 } else { 7 };
-                let max = if is_white { 6 } else { 12 };
-
-                if x >= 2 {
-                    if y >= 1 {
-                        to = index - 10; if self.board[to] == Piece::Empty || ((self.board[to] as u8) >= min && (self.board[to] as u8) <= max) { in_array.push(to); }
-                    }
-                    if y <= xy_max - 1 {
-                        to = index + 6;  if self.board[to] == Piece::Empty || ((self.board[to] as u8) >= min && (self.board[to] as u8) <= max) { in_array.push(to); }
-                    }
-                }
-                if x <= xy_max - 2 {
-                    if y <= xy_max - 1 {
-                        to = index + 10; if self.board[to] == Piece::Empty || ((self.board[to] as u8) >= min && (self.board[to] as u8) <= max) { in_array.push(to); }
-                    }
-                    if y >= 1 {
-                        to = index - 6;  if self.board[to] == Piece::Empty || ((self.board[to] as u8) >= min && (self.board[to] as u8) <= max) { in_array.push(to); }
-                    }
-                }
-                if y >= 2 {
-                    if x >= 1 {
-                        to = index - 17; if self.board[to] == Piece::Empty || ((self.board[to] as u8) >= min && (self.board[to] as u8) <= max) { in_array.push(to); }
-                    }
-                    if x <= xy_max - 1 {
-                        to = index - 15; if self.board[to] == Piece::Empty || ((self.board[to] as u8) >= min && (self.board[to] as u8) <= max) { in_array.push(to); }
-                    }
+                if attack_only {
+                    return attacks;
                 }
-                if y <= xy_max - 2 {
-                    if x <= xy_max - 1 {
-                        to = index + 17; if self.board[to] == Piece::Empty || ((self.board[to] as u8) >= min && (self.board[to] as u8) <= max) { in_array.push(to); }
-                    }
-                    if x >= 1 {
-                        to = index + 15; if self.board[to] == Piece::Empty || ((self.board[to] as u8) >= min && (self.board[to] as u8) <= max) { in_array.push(to); }
-                    }
-                }
-            },
 
-            _ => {}
+                let empty = !self.occupancy;
+                let from = square_bb(index);
+                let y = index / 8;
+                let pushes = if is_white {
+                    let single = (from >> 8) & empty;
+                    if y == 6 { single | ((single >> 8) & empty) } else { single }
+                } else {
+                    let single = (from << 8) & empty;
+                    if y == 1 { single | ((single << 8) & empty) } else { single }
+                };
+
+                pushes | (attacks & enemy)
+            },
+            Piece::Empty => EMPTY,
+            _ => attacks & !own
         }
     }
 
-    fn get_attacked_squares(&mut self, white: bool, to_index: i32, update_pins: bool, in_array: &mut Vec<usize>) {
+    // pieces of the given color attacking a square, using a custom occupancy for sliding pieces
+    fn attackers_to(&self, index: usize, by_white: bool, occupancy: Bitboard) -> Bitboard {
+        let offset = if by_white { 6 } else { 0 };
+        let bitboards = &self.bitboards;
+        let queens = bitboards[Piece::Queen_B as usize + offset];
+
+        (self.attack_tables.pawn(!by_white, index) & bitboards[Piece::Pawn_B as usize + offset]) |
+        (self.attack_tables.knight(index) & bitboards[Piece::Knight_B as usize + offset]) |
+        (self.attack_tables.king(index) & bitboards[Piece::King_B as usize + offset]) |
+        (self.attack_tables.bishop(index, occupancy) & (bitboards[Piece::Bishop_B as usize + offset] | queens)) |
+        (self.attack_tables.rook(index, occupancy) & (bitboards[Piece::Rook_B as usize + offset] | queens))
+    }
+
+    // squares attacked by the opponent of the given side
+    fn get_attacked_squares(&self, white: bool) -> Bitboard {
+        // the king is removed from the occupancy so it cannot escape along a checking ray
+        let king = self.bitboards[if white { Piece::King_W } else { Piece::King_B } as usize];
+        let occupancy = self.occupancy & !king;
+
         let start_index: usize = if white { 1 } else { 7 };
         let end_index: usize = if white { 6 } else { 12 };
+        let mut attacked = EMPTY;
         for i in start_index..=end_index {
-            let len = self.piece_locations[i].len();
-            for j in 0..len {
-                if self.piece_locations[i][j] as i32 == to_index { // when searching for valid moves, instead of modifying the piece dictionaries, just ignore any piece that would have been captured
-                    continue;
-                }
-                self.get_valid_squares(
-                    self.piece_locations[i][j],
-                    Piece::from_num(i as i32),
-                    true,
-                    update_pins,
-                    in_array
-                );
+            let piece = Piece::from_num(i as i32);
+            for location in squares(self.bitboards[i]) {
+                attacked |= self.get_attacks(location, piece, occupancy);
             }
         }
+        attacked
     }
 
-    // todo: do not trace if we know castling isnt possible
-    fn get_valid_castle_squares(&mut self, attacked_squares: &[usize], in_array: &mut Vec<EvalMove>) {
-        let mut traced: Vec<usize> = vec![];
-        if self.white_turn {
-            self.trace_valid_squares(
-                60,
-                1,
-                0,
-                true,
-                true,
-                false,
-                4,
-                7,
-                &mut traced
-            );
-            if !(self.castle_status & CastleStatus::WHITE_KING).is_empty() && self.board[63] == Piece::Rook_W && traced.len() == 2 {
-                if !attacked_squares.contains(&60) && !attacked_squares.contains(&61) && !attacked_squares.contains(&62) {
-                    in_array.push(EvalMove {
-                        from: 60,
-                        to: 62,
-                        data: Piece::Empty as i32,
-                        score: 0
-                    });
-                }
-            }
+    // pieces of the given side which are the only thing between their king and an enemy slider
+    fn pinned_pieces(&self, white: bool) -> Bitboard {
+        let king = self.bitboards[if white { Piece::King_W } else { Piece::King_B } as usize];
+        if king == EMPTY {
+            return EMPTY;
+        }
 
-            traced.clear();
+        let king_index = lsb(king);
+        let enemy_offset = if white { 0 } else { 6 };
+        let enemy_queens = self.bitboards[Piece::Queen_B as usize + enemy_offset];
+        let snipers = (self.attack_tables.rook(king_index, EMPTY) & (self.bitboards[Piece::Rook_B as usize + enemy_offset] | enemy_queens)) |
+                      (self.attack_tables.bishop(king_index, EMPTY) & (self.bitboards[Piece::Bishop_B as usize + enemy_offset] | enemy_queens));
 
-            self.trace_valid_squares(
-                60,
-                -1,
-                0,
-                true,
-                true,
-                false,
-                4,
-                7,
-                &mut traced
-            );
-            if !(self.castle_status & CastleStatus::WHITE_QUEEN).is_empty() && self.board[56] == Piece::Rook_W && traced.len() == 3 {
-                if !attacked_squares.contains(&60) && !attacked_squares.contains(&59) && !attacked_squares.contains(&58) {
-                    in_array.push(EvalMove {
-                        from: 60,
-                        to: 58,
-                        data: Piece::Empty as i32,
-                        score: 0
-                    });
-                }
+        let mut pinned = EMPTY;
+        for sniper in squares(snipers) {
+            let blockers = self.attack_tables.between(king_index, sniper) & self.occupancy;
+            if blockers.count_ones() == 1 {
+                pinned |= blockers & self.color_occupancy[white as usize];
             }
+        }
+        pinned
+    }
+
+    fn get_valid_castle_squares(&self, attacked_squares: Bitboard, in_array: &mut Vec<Move>) {
+        // (right, move, rook index, squares that must be empty, squares that cannot be attacked)
+        let castles = if self.white_turn {
+            [
+                (CastleStatus::WHITE_KING, Move::new(60, 62, MoveFlag::KING_CASTLE), 63, square_bb(61) | square_bb(62), square_bb(60) | square_bb(61) | square_bb(62)),
+                (CastleStatus::WHITE_QUEEN, Move::new(60, 58, MoveFlag::QUEEN_CASTLE), 56, square_bb(57) | square_bb(58) | square_bb(59), square_bb(60) | square_bb(59) | square_bb(58))
+            ]
         } else {
-            self.trace_valid_squares(
-                4,
-                1,
-                0,
-                false,
-                true,
-                false,
-                4,
-                0,
-                &mut traced
-            );
-            if !(self.castle_status & CastleStatus::BLACK_KING).is_empty() && self.board[7] == Piece::Rook_B && traced.len() == 2 {
-                if !attacked_squares.contains(&4) && !attacked_squares.contains(&5) && !attacked_squares.contains(&6) {
-                    in_array.push(EvalMove {
-                        from: 4,
-                        to: 6,
-                        data: Piece::Empty as i32,
-                        score: 0
-                    });
-                }
+            [
+                (CastleStatus::BLACK_KING, Move::new(4, 6, MoveFlag::KING_CASTLE), 7, square_bb(5) | square_bb(6), square_bb(4) | square_bb(5) | square_bb(6)),
+                (CastleStatus::BLACK_QUEEN, Move::new(4, 2, MoveFlag::QUEEN_CASTLE), 0, square_bb(1) | square_bb(2) | square_bb(3), square_bb(4) | square_bb(3) | square_bb(2))
+            ]
+        };
+        let rook = if self.white_turn { Piece::Rook_W } else { Piece::Rook_B };
+
+        for (flag, mov, rook_index, empty, safe) in castles.iter() {
+            if self.castle_status.contains(*flag) && self.board[*rook_index] == rook &&
+               self.occupancy & empty == EMPTY && attacked_squares & safe == EMPTY {
+                in_array.push(*mov);
             }
+        }
+    }
 
-            traced.clear();
+    pub fn is_in_check(&self, white: bool) -> bool {
+        let king = self.bitboards[if white { Piece::King_W } else { Piece::King_B } as usize];
+        king != EMPTY && self.attackers_to(lsb(king), !white, self.occupancy) != EMPTY
+    }
 
-            self.trace_valid_squares(
-                4,
-                -1,
-                0,
-                false,
-                true,
-                false,
-                4,
-                0,
-                &mut traced
-            );
-            if !(self.castle_status & CastleStatus::BLACK_QUEEN).is_empty() && self.board[0] == Piece::Rook_B && traced.len() == 3 {
-                if !attacked_squares.contains(&4) && !attacked_squares.contains(&3) && !attacked_squares.contains(&2) {
-                    in_array.push(EvalMove {
-                        from: 4,
-                        to: 2,
-                        data: Piece::Empty as i32,
-                        score: 0
-                    });
+    // anything besides the king and pawns, without which zugzwang is common
+    fn has_non_pawn_material(&self, white: bool) -> bool {
+        let pieces = if white {
+            [Piece::Queen_W, Piece::Rook_W, Piece::Bishop_W, Piece::Knight_W]
+        } else {
+            [Piece::Queen_B, Piece::Rook_B, Piece::Bishop_B, Piece::Knight_B]
+        };
+        pieces.iter().any(|p| self.bitboards[*p as usize] != EMPTY)
+    }
+
+    fn is_in_check_attacked_squares(&self, white: bool, attacked_squares: Bitboard) -> bool {
+        self.bitboards[if white { Piece::King_W } else { Piece::King_B } as usize] & attacked_squares != EMPTY
+    }
+
+    fn add_moves(in_array: &mut Vec<Move>, from_index: usize, targets: Bitboard, piece: Piece, enemy: Bitboard) {
+        let is_pawn = piece == Piece::Pawn_W || piece == Piece::Pawn_B;
+        for to_index in squares(targets) {
+            let capture = if has_square(enemy, to_index) { MoveFlag::CAPTURE } else { MoveFlag::QUIET };
+
+            // add more moves to account for promoting to various pieces
+            if is_pawn && has_square(RANK_1 | RANK_8, to_index) {
+                for promotion in [MoveFlag::QUEEN_PROMOTION, MoveFlag::ROOK_PROMOTION, MoveFlag::BISHOP_PROMOTION, MoveFlag::KNIGHT_PROMOTION] {
+                    in_array.push(Move::new(from_index, to_index, promotion | capture));
                 }
+            } else if is_pawn && from_index.abs_diff(to_index) == 16 {
+                in_array.push(Move::new(from_index, to_index, MoveFlag::DOUBLE_PAWN_PUSH));
+            } else {
+                in_array.push(Move::new(from_index, to_index, capture));
             }
         }
     }
 
-    pub fn is_in_check(&mut self, white: bool) -> bool {
-        let mut attacked_squares: Vec<usize> = vec![];
-        self.get_attacked_squares(
-            white,
-            -1,
-            false,
-            &mut attacked_squares
-        );
-        self.is_in_check_attacked_squares(
-            white,
-            &attacked_squares
-        )
-    }
-
-    fn is_in_check_attacked_squares(&self, white: bool, attacked_squares: &[usize]) -> bool {
-        (white && attacked_squares.contains(&self.piece_locations[Piece::King_W as usize][0])) || (!white && attacked_squares.contains(&self.piece_locations[Piece::King_B as usize][0]))
+    // en passant removes two pieces from the same rank, so look for a discovered attack on the king directly
+    fn is_legal_en_passant(&self, from_index: usize, to_index: usize, king_index: usize) -> bool {
+        let captured_index = if self.white_turn { to_index + 8 } else { to_index - 8 };
+        let occupancy = (self.occupancy & !square_bb(from_index) & !square_bb(captured_index)) | square_bb(to_index);
+        self.attackers_to(king_index, !self.white_turn, occupancy) & !square_bb(captured_index) == EMPTY
     }
 
     // cannot be pub since wasm_bindgen doesnt support returning struct vecs
-    fn get_all_valid_moves(&mut self, captures_only: bool, attacked_squares: &mut Vec<usize>) -> Vec<EvalMove> {
-        let mut all_valid: Vec<EvalMove> = vec![];
-
-        // todo: this doesnt always work since there might be no attacked squares and cause an extra check
-        if attacked_squares.len() == 0 {
-            self.pinned_pieces.clear();
-            self.get_attacked_squares(
-                self.white_turn,
-                -1,
-                true,
-                attacked_squares
-            );
-        }
+    // attacked_squares must come from get_attacked_squares for the current side
+    fn get_all_valid_moves(&mut self, captures_only: bool, attacked_squares: Bitboard) -> Vec<Move> {
+        let mut all_valid: Vec<Move> = vec![];
+
+        let white = self.white_turn;
+        let king = if white { Piece::King_W } else { Piece::King_B };
+        let own = self.color_occupancy[white as usize];
+        let enemy = self.color_occupancy[!white as usize];
+        let target_mask = if captures_only { enemy } else { !own };
+
+        // king moves only need to avoid attacked squares
+        let king_bitboard = self.bitboards[king as usize];
+        let king_index = if king_bitboard != EMPTY { Some(lsb(king_bitboard)) } else { None };
+        let mut checkers = EMPTY;
+        if let Some(king_index) = king_index {
+            let targets = self.attack_tables.king(king_index) & target_mask & !attacked_squares;
+            Engine::add_moves(&mut all_valid, king_index, targets, king, enemy);
+            checkers = self.attackers_to(king_index, !white, self.occupancy);
+        }
+
+        // in double check only the king can move
+        if checkers.count_ones() > 1 {
+            return all_valid;
+        }
+
+        // when in check, every other move has to capture the checker or block it
+        let check_mask = match king_index {
+            Some(king_index) if checkers != EMPTY => self.attack_tables.between(king_index, lsb(checkers)) | checkers,
+            _ => !EMPTY
+        };
 
-        if !captures_only {
+        if !captures_only && checkers == EMPTY {
             self.get_valid_castle_squares(
                 attacked_squares,
                 &mut all_valid
             );
         }
 
-        let in_check = self.is_in_check_attacked_squares(self.white_turn, &attacked_squares);
-        let start_index = if self.white_turn { 7 } else { 1 };
-        let end_index = if self.white_turn { 12 } else { 6 };
-        let mut local_valid: Vec<usize> = vec![];
-        let mut local_attacked: Vec<usize> = vec![];
+        let pinned = self.pinned_pieces(white);
+        let en_passant = if self.en_passant_square != -1 { square_bb(self.en_passant_square as usize) } else { EMPTY };
+        let start_index = if white { 8 } else { 2 }; // kings were handled above
+        let end_index = if white { 12 } else { 6 };
         for i in start_index..=end_index {
-            let len = self.piece_locations[i].len();
             let piece: Piece = Piece::from_num(i as i32);
+            let is_pawn = piece == Piece::Pawn_W || piece == Piece::Pawn_B;
+
+            for location in squares(self.bitboards[i]) {
+                // pinned pieces can only move along the line through their king
+                let mut pin_mask = !EMPTY;
+                if let Some(king_index) = king_index {
+                    if has_square(pinned, location) {
+                        pin_mask = self.attack_tables.line(king_index, location);
+                    }
+                }
 
-            for j in 0..len {
-                let location = self.piece_locations[i][j];
-
-                local_valid.clear();
-                self.get_valid_squares(
+                let valid = self.get_valid_squares(
                     location,
                     piece,
-                    captures_only,
-                    false,
-                    &mut local_valid
-                );
-
-                // make some assumptions but for some instances, simulate the move and do a double check that we arent being put in check
-                let is_pinned = self.pinned_pieces.contains(&location);
-                let local_valid_len = local_valid.len();
-                for k in 0..local_valid_len {
-                    let checking_index = local_valid[k];
-
-                    if captures_only && self.board[checking_index] == Piece::Empty {
-                        continue;
-                    }
-
-                    let is_en_passant = checking_index as i32 == self.en_passant_square; 
-                    if is_en_passant || in_check || is_pinned || piece == Piece::King_W || piece == Piece::King_B {
-                        // move the piece
-                        let piece_backup = self.board[checking_index];
-                        let second_backup = self.board[location];
-                        self.board[checking_index] = piece;
-                        self.board[location] = Piece::Empty;
-                        if is_en_passant {
-                            if piece == Piece::Pawn_W {
-                                self.board[checking_index + 8] = Piece::Empty;
-                            } else if piece == Piece::Pawn_B {
-                                self.board[checking_index - 8] = Piece::Empty;
-                            }
-                        }
-
-                        // get the attacked squares
-                        local_attacked.clear();
-                        self.get_attacked_squares(
-                            self.white_turn,
-                            checking_index as i32,
-                            false,
-                            &mut local_attacked
-                        );
-
-                        // move the pieces back
-                        self.board[checking_index] = piece_backup;
-                        self.board[location] = second_backup;
-                        if is_en_passant {
-                            if piece == Piece::Pawn_W {
-                                self.board[checking_index + 8] = Piece::Pawn_B;
-                            } else if piece == Piece::Pawn_B {
-                                self.board[checking_index - 8] = Piece::Pawn_W;
-                            }
-                        }
-
-                        if piece == Piece::King_W || piece == Piece::King_B {
-                            if local_attacked.contains(&checking_index) {
-                                continue;
-                            }
-                        } else if self.is_in_check_attacked_squares(self.white_turn, &local_attacked) {
-                            continue;
-                        }
-                    }
-
-                    // add more moves to account for promoting to various pieces
-                    let y: usize = (checking_index as f32 * 0.125) as usize;
-                    if piece == Piece::Pawn_W && y == 0 {
-                        all_valid.push(EvalMove {
-                            from: location as i32,
-                            to: checking_index as i32,
-                            data: Piece::Queen_W as i32,
-                            score: 0
-                        });
-                        all_valid.push(EvalMove {
-                            from: location as i32,
-                            to: checking_index as i32,
-                            data: Piece::Rook_W as i32,
-                            score: 0
-                        });
-                        all_valid.push(EvalMove {
-                            from: location as i32,
-                            to: checking_index as i32,
-                            data: Piece::Bishop_W as i32,
-                            score: 0
-                        });
-                        all_valid.push(EvalMove {
-                            from: location as i32,
-                            to: checking_index as i32,
-                            data: Piece::Knight_W as i32,
-                            score: 0
-                        });
-                    } else if piece == Piece::Pawn_B && y == 7 {
-                        all_valid.push(EvalMove {
-                            from: location as i32,
-                            to: checking_index as i32,
-                            data: Piece::Queen_B as i32,
-                            score: 0
-                        });
-                        all_valid.push(EvalMove {
-                            from: location as i32,
-                            to: checking_index as i32,
-                            data: Piece::Rook_B as i32,
-                            score: 0
-                        });
-                        all_valid.push(EvalMove {
-                            from: location as i32,
-                            to: checking_index as i32,
-                            data: Piece::Bishop_B as i32,
-                            score: 0
-                        });
-                        all_valid.push(EvalMove {
-                            from: location as i32,
-                            to: checking_index as i32,
-                            data: Piece::Knight_B as i32,
-                            score: 0
-                        });
-                    } else {
-                        all_valid.push(EvalMove {
-                            from: location as i32,
-                            to: checking_index as i32,
-                            data: Piece::Empty as i32,
-                            score: 0
-                        });
+                    captures_only
+                ) & target_mask & check_mask & pin_mask;
+                Engine::add_moves(&mut all_valid, location, valid, piece, enemy);
+
+                if is_pawn && self.get_attacks(location, piece, self.occupancy) & en_passant != EMPTY {
+                    let legal = match king_index {
+                        Some(king_index) => self.is_legal_en_passant(location, self.en_passant_square as usize, king_index),
+                        None => true
+                    };
+                    if legal {
+                        all_valid.push(Move::new(location, self.en_passant_square as usize, MoveFlag::EN_PASSANT));
                     }
                 }
             }
@@ -1414,103 +1041,91 @@ impl Engine {
         all_valid
     }
 
+    // kept for the js frontend, see perft
     pub fn calculate_all_possible_moves(&mut self, depth: i32) -> i32 {
+        self.perft(depth, false) as i32
+    }
+
+    // counts leaf nodes of the legal move tree. bulk_count stops one ply early and counts the generated moves
+    // instead of playing them, which is only valid because the generator is strictly legal
+    pub fn perft(&mut self, depth: i32, bulk_count: bool) -> u64 {
         if depth <= 0 {
             return 1;
         }
 
         let valid_moves = self.get_all_valid_moves(
             false,
-            &mut vec![]
+            self.get_attacked_squares(self.white_turn)
         );
+        if bulk_count && depth == 1 {
+            return valid_moves.len() as u64;
+        }
 
-        let mut total_moves = 0;
-        let starting_hash = self.board_hash;
-        let starting_en_passant = self.en_passant_square;
-        let starting_castle_status = self.castle_status;
-        let len = valid_moves.len();
-        for i in 0..len {
-            self.update_castle_status(
-                valid_moves[i].from as usize,
-                valid_moves[i].to as usize
-            );
+        let mut total_nodes = 0;
+        for mov in valid_moves.iter() {
+            total_nodes += self.perft_move(*mov, depth, bulk_count);
+        }
 
-            self.force_make_move(
-                valid_moves[i].from as usize,
-                &MoveInfo {
-                    index: valid_moves[i].to as usize,
-                    data: Piece::from_num(i as i32)
-                },
-                false
-            );
-
-            let mut stored_deltas = vec![];
-            swap(&mut stored_deltas, &mut self.board_deltas);
-
-            self.white_turn = !self.white_turn;
-            self.board_hash = self.update_hash(
-                stored_deltas.as_slice(),
-                starting_hash,
-                starting_en_passant,
-                starting_castle_status
-            );
+        total_nodes
+    }
 
-            total_moves += self.calculate_all_possible_moves(depth - 1);
+    fn perft_move(&mut self, mov: Move, depth: i32, bulk_count: bool) -> u64 {
+        let undo = self.make_move(mov);
+        let nodes = self.perft(depth - 1, bulk_count);
+        self.unmake_move(undo);
 
-            self.unmake_move(stored_deltas.as_slice(), starting_en_passant);
-            self.board_hash = starting_hash;
-            self.en_passant_square = starting_en_passant;
-            self.castle_status = starting_castle_status;
-        }
-
-        total_moves
+        nodes
     }
 
-    fn predict_and_order_moves(&self, moves: &mut Vec<EvalMove>, attacked_squares: &Vec<usize>) {
-        let len = moves.len();
+    fn predict_and_order_moves(&self, moves: &[Move], attacked_squares: Bitboard) -> Vec<ScoredMove> {
+        let mut scored_moves: Vec<ScoredMove> = Vec::with_capacity(moves.len());
 
-        for i in 0..len {
+        for (i, mov) in moves.iter().enumerate() {
             let mut score = 0;
-            let moving_piece = self.board[moves[i].from as usize];
-            let capturing_piece = self.board[moves[i].to as usize];
-            let promoting = Piece::from_num(moves[i].data);
+            let moving_piece = self.board[mov.from()];
 
-            if capturing_piece != Piece::Empty {
-                score += 10 * Engine::get_piece_value(capturing_piece) - Engine::get_piece_value(moving_piece);
+            if mov.is_en_passant() {
+                score += 10 * Engine::get_piece_value(Piece::Pawn_W) - Engine::get_piece_value(moving_piece);
+            } else if mov.is_capture() {
+                score += 10 * Engine::get_piece_value(self.board[mov.to()]) - Engine::get_piece_value(moving_piece);
             }
 
             // deprioritize moving into attacked squares
-            if attacked_squares.contains(&(moves[i].to as usize)) {
+            if has_square(attacked_squares, mov.to()) {
                 score -= Engine::get_piece_value(moving_piece);
             }
 
             // score promotion moves
-            if moving_piece == Piece::Pawn_W || moving_piece == Piece::Pawn_B {
-                score += Engine::get_piece_value(promoting);
-            }
+            score += Engine::get_piece_value(mov.promotion_piece());
 
-            score += Engine::read_square_table_value(moves[i].to as usize, Engine::get_piece_table(moving_piece), self.white_turn);
+            score += Engine::read_square_table_value(mov.to(), Engine::get_piece_table(moving_piece), self.white_turn);
 
-            moves[i].score = score;
+            scored_moves.push(ScoredMove {
+                mov: *mov,
+                score
+            });
 
             // sorting
             let mut index = i;
-            let current_elem = moves[index];
-            while index > 0 && (current_elem.score > moves[index - 1].score || (current_elem.score == moves[index - 1].score && current_elem.from > moves[index - 1].from)) {
-                moves[index] = moves[index - 1];
+            let current_elem = scored_moves[index];
+            while index > 0 && (current_elem.score > scored_moves[index - 1].score || (current_elem.score == scored_moves[index - 1].score && current_elem.mov.from() > scored_moves[index - 1].mov.from())) {
+                scored_moves[index] = scored_moves[index - 1];
                 index -= 1;
             }
-            moves[index] = current_elem;
+            scored_moves[index] = current_elem;
         }
 
-        //moves.sort_by_key(|a| a.score);
+        scored_moves
     }
 
     pub fn find_best_move(&mut self, can_cancel: bool, depth: i32, offset: i32, alpha: i32, beta: i32) -> i32 {
         let mut alpha = alpha;
         let mut beta = beta;
+        self.pv_table.clear(offset as usize);
+        self.nodes_searched += 1;
+        let after_null_move = replace(&mut self.after_null_move, false);
 
-        if can_cancel && Date::now() - self.search_start_time >= self.search_max_time {
+        if can_cancel && self.search_cancelled() {
             return 0;
         }
 
@@ -1532,47 +1147,71 @@ impl Engine {
             return alpha;
         }
 
-        match self.saved_evaluations.get(&self.board_hash) {
-            Some(saved_eval) => {
-                let mut should_return = false;
-                if saved_eval.depth >= depth {
-                    if saved_eval.saved_type == SavedEvalType::Exact { // exact eval was saved so just return it
-                        should_return = true;
-                    } else if saved_eval.saved_type == SavedEvalType::Alpha && saved_eval.eval <= alpha { // if we are storing the lower bound, only search if it is greater than the current lower bound
-                        should_return = true;
-                    } else if saved_eval.saved_type == SavedEvalType::Beta && saved_eval.eval >= beta { // if we are storing the upper bound, only search if it is less than the current upper bound
-                        should_return = true;
-                    }
+        // the table knows nothing about excluded moves, so the root of a multipv search neither reads nor writes it
+        let excluding = offset == 0 && !self.excluded_root_moves.is_empty();
+
+        let saved_eval = self.transposition_table.probe(self.board_hash, offset).filter(|_| !excluding);
+        if let Some(saved_eval) = saved_eval {
+            let mut should_return = false;
+            if saved_eval.depth() >= depth {
+                if saved_eval.bound() == SavedEvalType::Exact { // exact eval was saved so just return it
+                    should_return = true;
+                } else if saved_eval.bound() == SavedEvalType::Alpha && saved_eval.score() <= alpha { // if we are storing the lower bound, only search if it is greater than the current lower bound
+                    should_return = true;
+                } else if saved_eval.bound() == SavedEvalType::Beta && saved_eval.score() >= beta { // if we are storing the upper bound, only search if it is less than the current upper bound
+                    should_return = true;
                 }
-                if should_return {
-                    if offset == 0 {
-                        self.best_move_this_iteration = saved_eval.best_move;
-                        self.best_move_this_iteration.score = saved_eval.eval;
-                    }
-                    return saved_eval.eval;
+            }
+            if should_return {
+                if offset == 0 {
+                    self.set_root_best_move(ScoredMove {
+                        mov: saved_eval.mov(),
+                        score: saved_eval.score()
+                    });
                 }
-            },
-            None => {}
+                return saved_eval.score();
+            }
         }
 
-        self.pinned_pieces.clear();
-        let mut attacked_squares: Vec<usize> = vec![];
-        self.get_attacked_squares(
-            self.white_turn, 
-            -1, 
-            true,
-            &mut attacked_squares
+        let attacked_squares = self.get_attacked_squares(self.white_turn);
+        let in_check = self.is_in_check_attacked_squares(
+            self.white_turn,
+            attacked_squares
         );
+
+        // if passing the turn still fails high, so would the best move, except in zugzwang
+        // which is likely in check and with only pawns left, and two passes in a row prove nothing
+        if offset > 0
+            && depth > NULL_MOVE_REDUCTION + 1 // straight into quiescence would miss every quiet threat
+            && !in_check
+            && !after_null_move
+            && !is_mate_score(beta)
+            && self.has_non_pawn_material(self.white_turn)
+            && self.evaluate() >= beta
+        {
+            let undo = self.make_null_move();
+            self.after_null_move = true;
+            let evaluation = -self.find_best_move(
+                can_cancel,
+                depth - 1 - NULL_MOVE_REDUCTION - depth / 6,
+                offset + 1,
+                -beta,
+                -beta + 1
+            );
+            self.after_null_move = false;
+            self.unmake_null_move(undo);
+
+            if evaluation >= beta {
+                return beta;
+            }
+        }
+
         let mut valid_moves = self.get_all_valid_moves(
             false,
-            &mut attacked_squares
+            attacked_squares
         );
 
-        if valid_moves.len() == 0 { // either checkmate or stalemate
-            let in_check = self.is_in_check_attacked_squares(
-                self.white_turn,
-                &attacked_squares
-            );
+        if valid_moves.is_empty() { // either checkmate or stalemate
             if in_check {
                 return SMALLEST_SAFE_I32 + offset; // checkmate, worst possible move
             } else {
@@ -1593,127 +1232,298 @@ impl Engine {
             }
         }
 
-        self.predict_and_order_moves(
-            &mut valid_moves,
-            &attacked_squares
+        let mut ordered_moves = self.predict_and_order_moves(
+            &valid_moves,
+            attacked_squares
         );
+        // the best move of an earlier search of this position is the most likely to be best again
+        if let Some(index) = saved_eval.and_then(|e| ordered_moves.iter().position(|m| m.mov == e.mov())) {
+            ordered_moves[..=index].rotate_right(1);
+        }
 
-        let starting_hash = self.board_hash;
-        let starting_en_passant = self.en_passant_square;
-        let starting_castle_status = self.castle_status;
-        let mut best_move_for_this_position: EvalMove = Default::default();
+        let mut best_move_for_this_position = Move::NULL;
         let mut saving_type = SavedEvalType::Alpha;
-        for mov in valid_moves.iter() {
-            // make the move (todo: move to function)
-            self.update_castle_status(
-                mov.from as usize,
-                mov.to as usize
-            );
-            self.force_make_move(
-                mov.from as usize, 
-                &MoveInfo {
-                    index: mov.to as usize,
-                    data: Piece::from_num(mov.data)
-                },
-                false
-            );
-            let mut stored_deltas = vec![];
-            swap(&mut stored_deltas, &mut self.board_deltas);
-
-            self.white_turn = !self.white_turn;
-            self.board_hash = self.update_hash(
-                stored_deltas.as_slice(),
-                starting_hash,
-                starting_en_passant,
-                starting_castle_status
-            );
-
-            let evaluation = -1 * self.find_best_move(
-                can_cancel, 
-                depth - 1,
-                offset + 1,
-                -beta,
-                -alpha
-            );
+        let mut moves_searched = 0;
+        for scored_move in ordered_moves.iter() {
+            let mov = scored_move.mov;
+            if excluding && self.excluded_root_moves.contains(&mov) {
+                continue;
+            }
 
-            // unmake the move
-            self.unmake_move(&stored_deltas, starting_en_passant);
-            self.board_hash = starting_hash;
-            self.en_passant_square = starting_en_passant;
-            self.castle_status = starting_castle_status;
+            let undo = self.make_move(mov);
+
+            // quiet moves ordered late rarely turn out best, so they are searched shallower first
+            // and only get the full depth if they beat alpha anyway, every root move gets the full depth
+            let reduction = if offset > 0
+                && depth >= LMR_MIN_DEPTH
+                && moves_searched >= LMR_FULL_DEPTH_MOVES
+                && !in_check
+                && !mov.is_capture()
+                && !mov.is_promotion()
+                && !self.is_in_check(self.white_turn)
+            {
+                if moves_searched >= 4 * LMR_FULL_DEPTH_MOVES && depth >= 2 * LMR_MIN_DEPTH { 2 } else { 1 }
+            } else {
+                0
+            };
+
+            // principal variation search: after the first move the rest only have to be proven worse,
+            // which a null window does cheaply, and any that is not gets searched again properly
+            let mut evaluation;
+            if moves_searched == 0 {
+                evaluation = -self.find_best_move(
+                    can_cancel,
+                    depth - 1,
+                    offset + 1,
+                    -beta,
+                    -alpha
+                );
+            } else {
+                evaluation = -self.find_best_move(
+                    can_cancel,
+                    depth - 1 - reduction,
+                    offset + 1,
+                    -alpha - 1,
+                    -alpha
+                );
+                if reduction > 0 && evaluation > alpha {
+                    evaluation = -self.find_best_move(
+                        can_cancel,
+                        depth - 1,
+                        offset + 1,
+                        -alpha - 1,
+                        -alpha
+                    );
+                }
+                if evaluation > alpha && evaluation < beta {
+                    evaluation = -self.find_best_move(
+                        can_cancel,
+                        depth - 1,
+                        offset + 1,
+                        -beta,
+                        -alpha
+                    );
+                }
+            }
+            self.unmake_move(undo);
+            moves_searched += 1;
 
             // calc alpha & beta
             if evaluation >= beta {
-                self.saved_evaluations.insert(
-                    self.board_hash,
-                    EvaluationData {
-                        total_moves: 0,
-                        depth,
-                        best_move: best_move_for_this_position,
-                        saved_type: SavedEvalType::Beta,
-                        eval: beta
-                    }
-                );
+                // an aborted search returns made up scores which must not outlive it in the table
+                if !(excluding || can_cancel && self.search_cancelled()) {
+                    self.transposition_table.store(self.board_hash, mov, beta, depth, SavedEvalType::Beta, offset);
+                }
                 return beta;
             }
             if evaluation > alpha { // best move found
                 alpha = evaluation;
-                best_move_for_this_position = *mov;
+                best_move_for_this_position = mov;
                 saving_type = SavedEvalType::Exact;
+                self.pv_table.update(offset as usize, mov);
 
                 if offset == 0 {
-                    self.best_move_this_iteration = best_move_for_this_position;
-                    self.best_move_this_iteration.score = evaluation;
+                    self.set_root_best_move(ScoredMove {
+                        mov,
+                        score: evaluation
+                    });
                     self.moves_found_this_iteration.push(DebugMoveOutput {
-                        mov: self.best_move_this_iteration,
-                        piece: self.board[self.best_move_this_iteration.from as usize] as i32,
-                        capture: if self.board[self.best_move_this_iteration.to as usize] != Piece::Empty { 1 } else { 0 }
+                        mov: EvalMove::new(mov, evaluation),
+                        piece: self.board[mov.from()] as i32,
+                        capture: mov.is_capture() as i32
                     });
                 }
             }
         }
 
-        self.saved_evaluations.insert(
-            self.board_hash,
-            EvaluationData {
-                total_moves: 0,
-                depth,
-                best_move: best_move_for_this_position,
-                saved_type: saving_type,
-                eval: alpha
-            }
-        );
-        
+        if !(excluding || can_cancel && self.search_cancelled()) {
+            self.transposition_table.store(self.board_hash, best_move_for_this_position, alpha, depth, saving_type, offset);
+        }
+
         alpha
     }
 
-    pub fn find_best_move_iterative(&mut self) {
-        self.search_start_time = Date::now();
-        let max_depth = 30;
-        let mut last_completed_depth = 0;
+    // counts a different move than the last one found, or the last iteration's best for the first
+    fn set_root_best_move(&mut self, best: ScoredMove) {
+        let previous = if self.best_move_this_iteration.mov.is_null() { self.last_iteration_best } else { self.best_move_this_iteration.mov };
+        if !previous.is_null() && best.mov != previous {
+            self.best_move_changes += 1;
+        }
+        self.best_move_this_iteration = best;
+    }
 
-        for i in 1..=max_depth {
-            self.find_best_move(
+    // searches the root in a window around guess, widening whichever side the score falls outside of
+    fn aspiration_search(&mut self, depth: i32, guess: Option<i32>) {
+        let mut window = ASPIRATION_WINDOW;
+        let (mut alpha, mut beta) = match guess {
+            Some(score) if !is_mate_score(score) => (
+                max(score.saturating_sub(window), SMALLEST_SAFE_I32),
+                min(score.saturating_add(window), LARGEST_SAFE_I32)
+            ),
+            _ => (SMALLEST_SAFE_I32, LARGEST_SAFE_I32)
+        };
+
+        loop {
+            self.best_move_this_iteration = Default::default();
+            self.best_move_changes = 0;
+            let score = self.find_best_move(
                 true,
-                i,
+                depth,
                 0,
-                SMALLEST_SAFE_I32,
-                LARGEST_SAFE_I32
+                alpha,
+                beta
             );
+            if self.search_cancelled() {
+                return;
+            }
 
-            if Date::now() - self.search_start_time >= self.search_max_time { // search aborted so dont update move
+            // the search fails hard, so the score is the bound itself and says nothing about how far out the real score is
+            window = window.saturating_mul(4);
+            if score <= alpha && alpha > SMALLEST_SAFE_I32 {
+                alpha = max(alpha.saturating_sub(window), SMALLEST_SAFE_I32);
+            } else if score >= beta && beta < LARGEST_SAFE_I32 {
+                beta = min(beta.saturating_add(window), LARGEST_SAFE_I32);
+            } else {
+                return;
+            }
+        }
+    }
+
+    // the pv table's line starting with first, continued from the transposition table where it was cut short by a table hit
+    // up to depth moves long
+    fn collect_principal_variation(&mut self, first: Move, depth: i32) -> Vec<Move> {
+        let mut line = self.pv_table.line().to_vec();
+        if line.first() != Some(&first) { // the root itself came from the table
+            line = vec![first];
+        }
+
+        let mut undos: Vec<UndoInfo> = line.iter().map(|m| self.make_move(*m)).collect();
+        while (line.len() as i32) < depth {
+            let mov = match self.transposition_table.probe(self.board_hash, line.len() as i32) {
+                Some(entry) if !entry.mov().is_null() => entry.mov(),
+                _ => break
+            };
+            // the entry may belong to another position with the same bucket and key
+            if !self.legal_moves().contains(&mov) || undos.iter().any(|u| u.board_hash == self.board_hash) {
+                break;
+            }
+            line.push(mov);
+            undos.push(self.make_move(mov));
+        }
+        while let Some(undo) = undos.pop() {
+            self.unmake_move(undo);
+        }
+
+        line
+    }
+
+    // without a second line to compare with, a null window search one ply shallower and without the best move
+    // tells whether every other move is at least CLEAR_MARGIN worse
+    fn single_pv_margin(&mut self, depth: i32) -> Option<i32> {
+        if is_mate_score(self.best_move.score) {
+            return None;
+        }
+        let bound = self.best_move.score.saturating_sub(CLEAR_MARGIN);
+        self.excluded_root_moves.push(self.best_move.mov);
+        let score = self.find_best_move(true, max(depth - 1, 1), 0, bound - 1, bound);
+        self.excluded_root_moves.clear();
+
+        // the search fails hard, so all it tells is which side of the bound the other moves are on
+        if self.search_cancelled() {
+            None
+        } else if score < bound {
+            Some(CLEAR_MARGIN)
+        } else {
+            Some(CLEAR_MARGIN - 1)
+        }
+    }
+
+    fn search_cancelled(&self) -> bool {
+        self.clock.now() - self.search_start_time >= self.search_budget.hard || self.stop_signal.load(Ordering::Relaxed)
+    }
+
+    pub fn find_best_move_iterative(&mut self) {
+        self.search_start_time = self.clock.now();
+        if let Some(chess_clock) = self.chess_clock.as_ref() {
+            self.search_budget = chess_clock.allocate(self.white_turn);
+        }
+        let mut time_manager = TimeManager::new(self.search_budget);
+        self.transposition_table.new_search();
+        let forced = self.legal_moves().len() == 1;
+        let mut last_completed_depth = 0;
+        self.nodes_searched = 0;
+        self.last_iteration_best = Move::NULL;
+
+        self.pv_lines.clear();
+
+        for i in 1..=self.search_max_depth {
+            let iteration_start = self.clock.now();
+            let mut lines = vec![];
+            let mut best_move_changes = 0;
+
+            // each further line is the best move once the moves of the earlier lines are excluded
+            while lines.len() < self.multi_pv {
+                // only the best line has a score from the last iteration to aim at
+                let guess = (lines.is_empty() && last_completed_depth > 0).then_some(self.best_move.score);
+                self.aspiration_search(i, guess);
+                if self.search_cancelled() || self.best_move_this_iteration.mov.is_null() { // no root moves are left
+                    break;
+                }
+
+                if lines.is_empty() {
+                    best_move_changes = self.best_move_changes;
+                }
+                let best = self.best_move_this_iteration;
+                let moves = self.collect_principal_variation(best.mov, i);
+                let san = self.line_to_san(&moves);
+                lines.push(PvLine::new(best, i, moves, san));
+                self.excluded_root_moves.push(best.mov);
+            }
+            self.excluded_root_moves.clear();
+
+            if self.search_cancelled() || lines.is_empty() { // search aborted so dont update move
                break;
             }
 
             last_completed_depth = i;
-            self.best_move = self.best_move_this_iteration;
+            lines.sort_by_key(|l| Reverse(l.score)); // stable, so equal scores keep the order they were found in
+            self.best_move = ScoredMove {
+                mov: lines[0].moves[0],
+                score: lines[0].score
+            };
+            self.last_iteration_best = self.best_move.mov;
+            self.principal_variation = lines[0].moves.clone();
+            self.pv_lines = lines;
             swap(&mut self.moves_found_this_iteration, &mut self.moves_found_this_turn);
             self.moves_found_this_iteration.clear();
 
-            // update eval on frontend if this is being run in the eval worker
-            post_eval_message(String::from(""), if self.white_turn { self.best_move.score } else { -self.best_move.score });
+            for (rank, line) in self.pv_lines.iter().enumerate() {
+                self.info_sink.post_info(&SearchInfo {
+                    depth: i,
+                    score: line.score,
+                    white_turn: self.white_turn,
+                    time: self.clock.now() - self.search_start_time,
+                    best_move: line.moves[0],
+                    pv: line.moves.clone(),
+                    pv_san: line.san.clone(),
+                    multipv: rank + 1,
+                    nodes: self.nodes_searched
+                });
+            }
+
+            if self.multi_pv == 1 && is_mate_score(self.best_move.score) { // every line up to this depth was searched, so no shorter mate exists
+                break;
+            }
+            if forced { // any depth gives the same answer
+                break;
+            }
 
-            if self.best_move.score >= 99999999 { // mate
+            let margin = match self.pv_lines.get(1) {
+                Some(second) => Some(self.best_move.score.saturating_sub(second.score)),
+                None => self.single_pv_margin(i)
+            };
+            let now = self.clock.now();
+            if !time_manager.should_continue(self.best_move.mov, self.best_move.score, best_move_changes, margin, now - self.search_start_time, now - iteration_start) {
                 break;
             }
         }
@@ -1723,6 +1533,7 @@ impl Engine {
 
     // search until the position is 'quiet' (no captures remaining)
     pub fn quiescence_search(&mut self, alpha: i32, beta: i32) -> i32 {
+        self.nodes_searched += 1;
         let evaluation = self.evaluate(); // evaluate first to prevent forcing a bad capture when there may have been better non capture moves
         let mut alpha = alpha;
 
@@ -1733,49 +1544,24 @@ impl Engine {
             alpha = evaluation;
         }
 
-        self.pinned_pieces.clear();
-        let mut attacked_squares: Vec<usize> = vec![];
-        self.get_attacked_squares(
-            self.white_turn, 
-            -1, 
-            true,
-            &mut attacked_squares
-        );
-        let mut valid_moves = self.get_all_valid_moves(
+        let attacked_squares = self.get_attacked_squares(self.white_turn);
+        let valid_moves = self.get_all_valid_moves(
             true,
-            &mut attacked_squares
+            attacked_squares
         );
 
-        self.predict_and_order_moves(
-            &mut valid_moves,
-            &attacked_squares
+        let ordered_moves = self.predict_and_order_moves(
+            &valid_moves,
+            attacked_squares
         );
 
-        let starting_en_passant = self.en_passant_square;
-        for mov in valid_moves.iter() {
-            // make the move (todo: move to function)
-            // dont update hash or castle status because they aren't relevant here
-            self.force_make_move(
-                mov.from as usize, 
-                &MoveInfo {
-                    index: mov.to as usize,
-                    data: Piece::from_num(mov.data)
-                },
-                false
-            );
-            let mut stored_deltas = vec![];
-            swap(&mut stored_deltas, &mut self.board_deltas);
-
-            self.white_turn = !self.white_turn;
-
-            let evaluation = -1 * self.quiescence_search(
+        for scored_move in ordered_moves.iter() {
+            let undo = self.make_move(scored_move.mov);
+            let evaluation = -self.quiescence_search(
                 -beta,
                 -alpha
             );
-
-            // unmake the move
-            self.unmake_move(&stored_deltas, starting_en_passant);
-            self.en_passant_square = starting_en_passant;
+            self.unmake_move(undo);
 
             if evaluation >= beta {
                 return beta;
@@ -1793,9 +1579,11 @@ impl Engine {
             return false;
         }
 
-        let start_time = Date::now();
+        let start_time = self.clock.now();
         self.moves_found_this_iteration.clear();
         self.moves_found_this_turn.clear();
+        self.transposition_table.new_search();
+        self.nodes_searched = 0;
 
         self.find_best_move(
             false,
@@ -1804,8 +1592,8 @@ impl Engine {
             SMALLEST_SAFE_I32,
             LARGEST_SAFE_I32
         );
-        if self.best_move.to == self.best_move_this_iteration.to && self.best_move.from == self.best_move_this_iteration.from { // repeating the same move from the last evaluatioin
-            log("Attempting to make the same move, aborting");
+        if self.best_move.mov == self.best_move_this_iteration.mov { // repeating the same move from the last evaluatioin
+            self.log_sink.log("Attempting to make the same move, aborting");
             return false;
         } else {
             self.best_move = self.best_move_this_iteration;
@@ -1813,24 +1601,16 @@ impl Engine {
 
         swap(&mut self.moves_found_this_turn, &mut self.moves_found_this_iteration);
         self.depth_searched_last_turn = depth;
-        self.castled_this_turn = self.update_castle_status(
-            self.best_move.from as usize,
-            self.best_move.to as usize
-        );
-        self.piece_captured_this_turn = self.board[self.best_move.to as usize] != Piece::Empty;
-        
-        if !threaded {
-            self.force_make_move(
-                self.best_move.from as usize,
-                &MoveInfo {
-                    index: self.best_move.to as usize,
-                    data: Piece::from_num(self.best_move.data)
-                },
-                true
-            );
+        self.principal_variation = self.collect_principal_variation(self.best_move.mov, depth);
+        if threaded {
+            self.last_move_san = self.move_to_san(self.best_move.mov);
+            self.castled_this_turn = self.best_move.mov.is_castle();
+            self.piece_captured_this_turn = self.best_move.mov.is_capture();
+        } else {
+            self.play_game_move(self.best_move.mov);
         }
 
-        let time_elapsed = Date::now() - start_time;
+        let time_elapsed = self.clock.now() - start_time;
         self.time_taken_last_turn = time_elapsed; // ms
 
         true
@@ -1841,73 +1621,257 @@ impl Engine {
             return false;
         }
 
-        let start_time = Date::now();
+        let start_time = self.clock.now();
         let last_move = self.best_move;
         self.moves_found_this_iteration.clear();
         self.moves_found_this_turn.clear();
 
         self.find_best_move_iterative();
-        if self.best_move.to == last_move.to && self.best_move.from == last_move.from { // repeating the same move from the last evaluatioin
-            log("Attempting to make the same move, aborting");
+        if self.best_move.mov == last_move.mov { // repeating the same move from the last evaluatioin
+            self.log_sink.log("Attempting to make the same move, aborting");
             return false;
         }
 
-        self.castled_this_turn = self.update_castle_status(
-            self.best_move.from as usize,
-            self.best_move.to as usize
-        );
-        self.piece_captured_this_turn = self.board[self.best_move.to as usize] != Piece::Empty;
-        self.force_make_move(
-            self.best_move.from as usize,
-            &MoveInfo {
-                index: self.best_move.to as usize,
-                data: Piece::from_num(self.best_move.data)
-            },
-            true
-        );
+        self.play_game_move(self.best_move.mov);
 
-        let time_elapsed = Date::now() - start_time;
+        let time_elapsed = self.clock.now() - start_time;
         self.time_taken_last_turn = time_elapsed; // ms
 
         true
     }
 
-    pub fn attempt_move(&mut self, from_index: usize, to_index: usize) -> bool {
+    // promotion is Piece::Empty unless the move promotes, in which case it must match the pawn's color
+    pub fn attempt_move(&mut self, from_index: usize, to_index: usize, promotion: Piece) -> Result<bool, MoveError> {
         let moving_piece = self.board[from_index];
 
         if self.check_for_draw() {
-            return false;
+            return Ok(false);
         }
 
         // no-op moves
         if from_index == to_index || moving_piece == Piece::Empty {
-            return false;
+            return Ok(false);
         }
 
         // only move correct color pieces on correct turn
         if (self.white_turn && moving_piece < Piece::King_W) || (!self.white_turn && moving_piece > Piece::Pawn_B) {
+            return Ok(false);
+        }
+
+        let valid_moves = self.get_all_valid_moves(false, self.get_attacked_squares(self.white_turn));
+        let candidates: Vec<Move> = valid_moves.into_iter().filter(|m| m.from() == from_index && m.to() == to_index).collect();
+        let mov = match candidates.first() {
+            None => return Ok(false),
+            Some(m) if !m.is_promotion() => {
+                if promotion != Piece::Empty {
+                    return Err(MoveError::InvalidPromotion(promotion));
+                }
+                *m
+            },
+            Some(_) if promotion == Piece::Empty => return Err(MoveError::MissingPromotion),
+            Some(_) => match candidates.iter().find(|m| m.promotion_piece() == promotion) {
+                Some(m) => *m,
+                None => return Err(MoveError::InvalidPromotion(promotion))
+            }
+        };
+
+        self.play_game_move(mov);
+
+        Ok(true)
+    }
+
+    // the legal move a san string refers to, without playing it
+    pub fn san_to_move(&mut self, san: &str) -> Result<EvalMove, SanError> {
+        let mov = self.move_from_san(san)?;
+        Ok(EvalMove::new(mov, 0))
+    }
+
+    // plays a move in uci notation such as e7e8n, returns false if the game is already drawn
+    pub fn attempt_move_uci(&mut self, uci: &str) -> Result<bool, UciError> {
+        if self.check_for_draw() {
+            return Ok(false);
+        }
+
+        let mov = self.parse_uci_move(uci)?;
+        self.play_game_move(mov);
+
+        Ok(true)
+    }
+
+    // the legal move a uci string refers to, without playing it
+    pub fn move_from_uci(&mut self, uci: &str) -> Result<EvalMove, UciError> {
+        let mov = self.parse_uci_move(uci)?;
+        Ok(EvalMove::new(mov, 0))
+    }
+
+    // steps back one move, it stays available to redo_move until a different move is played
+    pub fn undo_move(&mut self) -> bool {
+        match self.game.pop() {
+            Some(undo) => {
+                self.unmake_move(undo);
+                self.finish_history_step();
+                true
+            },
+            None => false
+        }
+    }
+
+    pub fn redo_move(&mut self) -> bool {
+        match self.game.next_move() {
+            Some(mov) => {
+                let undo = self.make_move(mov);
+                self.game.push(mov, undo);
+                self.finish_history_step();
+                true
+            },
+            None => false
+        }
+    }
+
+    // jumps to the position after ply moves of the current line
+    pub fn goto_ply(&mut self, ply: usize) -> bool {
+        let line = self.game.line();
+        if ply > line.len() {
             return false;
         }
 
-        let valid_moves = self.get_all_valid_moves(false, &mut vec![]);
-        if !valid_moves.iter().any(|m| m.from == from_index as i32 && m.to == to_index as i32) {
+        self.goto_node(if ply == 0 { Game::ROOT } else { line[ply - 1] })
+    }
+
+    // walks to any node of the game tree through their common ancestor
+    pub fn goto_node(&mut self, id: NodeId) -> bool {
+        if !self.game.contains(id) {
             return false;
         }
 
-        self.castled_this_turn = self.update_castle_status(from_index, to_index);
-        self.piece_captured_this_turn = self.board[to_index] != Piece::Empty; // todo: en passant capture noise doesn't work with this
-        self.force_make_move(
-            from_index,
-            &MoveInfo {
-                index: to_index,
-                data: if self.white_turn { Piece::Queen_W } else { Piece::Queen_B } // auto promote to queen when possible
-            },
-            true
-        );
+        let target = self.game.path(id);
+        while !target.starts_with(&self.game.path(self.game.current())) {
+            let undo = self.game.pop().unwrap();
+            self.unmake_move(undo);
+        }
+        for node in target[self.game.ply()..].iter() {
+            let mov = self.game.node(*node).mov;
+            let undo = self.make_move(mov);
+            self.game.push(mov, undo);
+        }
+        self.finish_history_step();
 
         true
     }
 
+    pub fn current_node(&self) -> NodeId {
+        self.game.current()
+    }
+
+    // the move that leads to a node as an EvalMove, without a score
+    pub fn node_move(&self, id: NodeId) -> Option<EvalMove> {
+        if id == Game::ROOT || !self.game.contains(id) {
+            return None;
+        }
+        Some(EvalMove::new(self.game.node(id).mov, 0))
+    }
+
+    pub fn node_parent(&self, id: NodeId) -> Option<NodeId> {
+        if !self.game.contains(id) {
+            return None;
+        }
+        self.game.node(id).parent()
+    }
+
+    // the first child continues the mainline
+    pub fn node_children(&self, id: NodeId) -> Vec<NodeId> {
+        if !self.game.contains(id) {
+            return vec![];
+        }
+        self.game.node(id).children().to_vec()
+    }
+
+    pub fn promote_to_mainline(&mut self, id: NodeId) -> bool {
+        if !self.game.contains(id) {
+            return false;
+        }
+        self.game.promote_to_mainline(id);
+        true
+    }
+
+    // removes a node and everything after it, leaving the branch first if it is being viewed
+    pub fn delete_branch(&mut self, id: NodeId) -> bool {
+        if id == Game::ROOT || !self.game.contains(id) {
+            return false;
+        }
+        if self.game.is_ancestor(id, self.game.current()) {
+            self.goto_node(self.game.node(id).parent().unwrap());
+        }
+        self.game.delete_branch(id);
+        true
+    }
+
+    pub fn node_comment(&self, id: NodeId) -> Option<String> {
+        if !self.game.contains(id) {
+            return None;
+        }
+        self.game.node(id).comment.clone()
+    }
+
+    pub fn set_node_comment(&mut self, id: NodeId, comment: Option<String>) -> bool {
+        if !self.game.contains(id) {
+            return false;
+        }
+        self.game.node_mut(id).comment = comment;
+        true
+    }
+
+    pub fn node_nags(&self, id: NodeId) -> Vec<u8> {
+        if !self.game.contains(id) {
+            return vec![];
+        }
+        self.game.node(id).nags.clone()
+    }
+
+    pub fn set_node_nags(&mut self, id: NodeId, nags: Vec<u8>) -> bool {
+        if !self.game.contains(id) {
+            return false;
+        }
+        self.game.node_mut(id).nags = nags;
+        true
+    }
+
+    // replaces the game with the first game of the pgn, positioned at its start
+    pub fn import_pgn(&mut self, pgn: &str) -> Result<(), PgnError> {
+        let pgn = PgnGame::parse(pgn)?;
+        self.parse_fen(pgn.start_fen())?;
+        self.game = Game::from_pgn(&pgn);
+        Ok(())
+    }
+
+    pub fn export_pgn(&self) -> Result<String, PgnError> {
+        Ok(PgnGame::from_game(&self.game)?.to_pgn())
+    }
+
+    // drops the undone moves so they can no longer be redone
+    pub fn truncate_game(&mut self) {
+        self.game.truncate();
+    }
+
+    pub fn game_ply(&self) -> usize {
+        self.game.ply()
+    }
+
+    pub fn game_length(&self) -> usize {
+        self.game.len()
+    }
+
+    pub fn move_to_uci(from_index: usize, to_index: usize, promotion: Piece) -> String {
+        let flags = match promotion {
+            Piece::Queen_W | Piece::Queen_B => MoveFlag::QUEEN_PROMOTION,
+            Piece::Rook_W | Piece::Rook_B => MoveFlag::ROOK_PROMOTION,
+            Piece::Bishop_W | Piece::Bishop_B => MoveFlag::BISHOP_PROMOTION,
+            Piece::Knight_W | Piece::Knight_B => MoveFlag::KNIGHT_PROMOTION,
+            _ => MoveFlag::QUIET
+        };
+        Move::new(from_index, to_index, flags).to_uci()
+    }
+
     pub fn board_ptr(&self) -> *const Piece {
         self.board.as_ptr()
     }
@@ -1916,9 +1880,11 @@ impl Engine {
         for (i, elem) in board.iter().enumerate() {
             self.board[i] = Piece::from_num(*elem);
         }
+        self.rebuild_bitboards();
     }
 
-    pub fn valid_moves_ptr(&self) -> *const EvalMove {
+    // packed 16 bit moves, see defs::Move
+    pub fn valid_moves_ptr(&self) -> *const Move {
         self.all_valid_moves.as_ptr()
     }
 
@@ -1935,11 +1901,11 @@ impl Engine {
     }
 
     pub fn castle_status(&self) -> u8 {
-        unsafe { transmute(self.castle_status) }
+        self.castle_status.bits()
     }
 
     pub fn set_castle_status(&mut self, castle_status: u8) {
-        self.castle_status = unsafe { transmute(castle_status) };
+        self.castle_status = CastleStatus::from_bits_truncate(castle_status);
     }
 
     pub fn en_passant_square(&self) -> i32 {
@@ -1983,16 +1949,23 @@ impl Engine {
 
     pub fn piece_locations(&self, piece: i32) -> Vec<i32> {
         let mut list: Vec<i32> = vec![];
-        for elem in self.piece_locations[piece as usize].iter() {
-            list.push(*elem as i32)
+        for elem in squares(self.bitboards[piece as usize]) {
+            list.push(elem as i32)
         }
         list
     }
 
     pub fn set_piece_locations(&mut self, piece: i32, locations: Vec<i32>) {
-        self.piece_locations[piece as usize].clear();
+        let piece = Piece::from_num(piece);
+        if piece == Piece::Empty {
+            return;
+        }
+
+        for elem in squares(self.bitboards[piece as usize]) {
+            self.set_square(elem, Piece::Empty);
+        }
         for elem in locations.iter() {
-            self.piece_locations[piece as usize].push(*elem as usize);
+            self.set_square(*elem as usize, piece);
         }
     }
 
@@ -2008,8 +1981,46 @@ impl Engine {
         self.castled_this_turn
     }
 
-    pub fn best_move(&self) -> *const EvalMove {
-        &self.best_move
+    pub fn last_move_san(&self) -> String {
+        self.last_move_san.clone()
+    }
+
+    // moves until mate for the best move's score, negative when the engine is getting mated
+    pub fn best_move_mate_in(&self) -> Option<i32> {
+        mate_in(self.best_move.score)
+    }
+
+    // the line expected after the best move's search, in uci notation separated by spaces
+    pub fn principal_variation_uci(&self) -> String {
+        self.principal_variation.iter().map(|m| m.to_uci()).collect::<Vec<String>>().join(" ")
+    }
+
+    // the same line as numbered san, e.g. "1. e4 e5 2. Nf3"
+    pub fn principal_variation_san(&mut self) -> String {
+        self.line_to_san(&self.principal_variation.clone())
+    }
+
+    // how many ranked lines find_best_move_iterative reports, 1 is a normal search
+    pub fn set_multi_pv(&mut self, lines: usize) {
+        self.multi_pv = lines.max(1);
+    }
+
+    pub fn multi_pv(&self) -> usize {
+        self.multi_pv
+    }
+
+    // fewer than multi_pv when the position has fewer legal moves
+    pub fn pv_line_count(&self) -> usize {
+        self.pv_lines.len()
+    }
+
+    // the lines of the last completed iteration, best first
+    pub fn pv_line(&self, index: usize) -> Option<PvLine> {
+        self.pv_lines.get(index).cloned()
+    }
+
+    pub fn best_move(&self) -> EvalMove {
+        EvalMove::new(self.best_move.mov, self.best_move.score)
     }
 
     pub fn time_taken_last_turn(&self) -> u32 {
@@ -2020,12 +2031,75 @@ impl Engine {
         self.depth_searched_last_turn
     }
 
+    pub fn nodes_searched(&self) -> u64 {
+        self.nodes_searched
+    }
+
     pub fn set_depth_searched_last_turn(&mut self, depth: i32) {
         self.depth_searched_last_turn = depth;
     }
 
+    // a fixed time per move, only used while no time control is set
     pub fn update_max_search_time(&mut self, time: u32) {
-        self.search_max_time = time;
+        self.search_budget = SearchBudget::fixed(time);
+    }
+
+    pub fn set_sudden_death(&mut self, base: u32) {
+        self.set_time_control(Some(TimeControl::SuddenDeath { base }));
+    }
+
+    pub fn set_fischer(&mut self, base: u32, increment: u32) {
+        self.set_time_control(Some(TimeControl::Fischer { base, increment }));
+    }
+
+    pub fn set_bronstein_delay(&mut self, base: u32, delay: u32) {
+        self.set_time_control(Some(TimeControl::Bronstein { base, delay }));
+    }
+
+    pub fn set_simple_delay(&mut self, base: u32, delay: u32) {
+        self.set_time_control(Some(TimeControl::SimpleDelay { base, delay }));
+    }
+
+    pub fn set_moves_to_go(&mut self, base: u32, moves: u32) {
+        self.set_time_control(Some(TimeControl::MovesToGo { base, moves }));
+    }
+
+    // goes back to the fixed time per move
+    pub fn clear_time_control(&mut self) {
+        self.set_time_control(None);
+    }
+
+    // ms left on a side's clock, none without a time control
+    pub fn clock_remaining(&self, white: bool) -> Option<u32> {
+        self.chess_clock.as_ref().map(|c| c.remaining(white))
+    }
+
+    pub fn set_clock_remaining(&mut self, white: bool, remaining: u32) {
+        if let Some(chess_clock) = self.chess_clock.as_mut() {
+            chess_clock.set_remaining(white, remaining);
+        }
+    }
+
+    pub fn is_flagged(&self, white: bool) -> bool {
+        self.chess_clock.as_ref().is_some_and(|c| c.is_flagged(white))
+    }
+
+    // reallocates the transposition table, which loses its contents
+    pub fn set_hash_size(&mut self, megabytes: usize) {
+        self.transposition_table = TranspositionTable::new(megabytes);
+    }
+
+    pub fn clear_hash(&mut self) {
+        self.transposition_table.clear();
+    }
+
+    // per mille of the transposition table filled by the last search
+    pub fn hash_full(&self) -> u32 {
+        self.transposition_table.hash_full()
+    }
+
+    pub fn update_max_search_depth(&mut self, depth: i32) {
+        self.search_max_depth = depth;
     }
 
     pub fn moves_found_this_turn_ptr(&self) -> *const DebugMoveOutput {
@@ -2047,4 +2121,221 @@ impl Engine {
     pub fn set_thread_index(&mut self, thread_index: i32) {
         self.thread_index = thread_index;
     }
-}
\ No newline at end of file
+}
+
+impl Default for Engine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// platform hooks and move types are not exposed to js since wasm_bindgen doesnt support trait objects or tuple structs
+impl Engine {
+    // starts both clocks from the base time, the side to move's clock runs from now
+    pub fn set_time_control(&mut self, time_control: Option<TimeControl>) {
+        self.chess_clock = time_control.map(ChessClock::new);
+        self.turn_start_time = self.clock.now();
+    }
+
+    pub fn chess_clock(&self) -> Option<&ChessClock> {
+        self.chess_clock.as_ref()
+    }
+
+    pub fn search_budget(&self) -> SearchBudget {
+        self.search_budget
+    }
+
+    // limits for the next search, replaced by the clock's allocation when a time control is set
+    pub fn set_search_budget(&mut self, budget: SearchBudget) {
+        self.search_budget = budget;
+    }
+
+    pub fn set_clock(&mut self, clock: Box<dyn Clock>) {
+        self.clock = clock;
+        self.turn_start_time = self.clock.now();
+    }
+
+    pub fn set_log_sink(&mut self, log_sink: Box<dyn LogSink>) {
+        self.log_sink = log_sink;
+    }
+
+    pub fn set_info_sink(&mut self, info_sink: Box<dyn InfoSink>) {
+        self.info_sink = info_sink;
+    }
+
+    // setting the returned flag aborts the current search as if it ran out of time
+    pub fn stop_signal(&self) -> Arc<AtomicBool> {
+        self.stop_signal.clone()
+    }
+
+    pub fn valid_moves(&self) -> &[Move] {
+        &self.all_valid_moves
+    }
+
+    pub fn last_best_move(&self) -> ScoredMove {
+        self.best_move
+    }
+
+    pub fn principal_variation(&self) -> &[Move] {
+        &self.principal_variation
+    }
+
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    // the move must be legal in the current position
+    // only the board state is updated, finish_turn refreshes what the frontend reads
+    pub fn make_move(&mut self, mov: Move) -> UndoInfo {
+        let from_index = mov.from();
+        let to_index = mov.to();
+        let moving_piece = self.board[from_index];
+        let captured_index = if mov.is_en_passant() {
+            if moving_piece == Piece::Pawn_W { to_index + 8 } else { to_index - 8 }
+        } else {
+            to_index
+        };
+
+        let undo = UndoInfo {
+            mov,
+            captured_piece: self.board[captured_index],
+            castle_status: self.castle_status,
+            en_passant_square: self.en_passant_square,
+            move_rep_count: self.move_rep_count,
+            board_hash: self.board_hash
+        };
+
+        // whether the square counts depends on the pawns, so it is taken out before they move and put back after
+        self.board_hash ^= self.hash_en_passant();
+
+        if undo.captured_piece != Piece::Empty {
+            self.board_hash ^= self.hash_piece(captured_index, undo.captured_piece);
+            self.set_square(captured_index, Piece::Empty);
+        }
+        self.move_piece(from_index, to_index);
+
+        if mov.is_promotion() {
+            let promotion = mov.promotion_piece();
+            self.board_hash ^= self.hash_piece(to_index, moving_piece) ^ self.hash_piece(to_index, promotion);
+            self.set_square(to_index, promotion);
+        }
+
+        if mov.is_castle() {
+            let (rook_from, rook_to) = Engine::castle_rook_squares(mov);
+            self.move_piece(rook_from, rook_to);
+        }
+
+        self.castle_status &= !(Engine::castle_rights_lost(from_index) | Engine::castle_rights_lost(to_index));
+        self.board_hash ^= self.hash_castle_status(undo.castle_status) ^ self.hash_castle_status(self.castle_status);
+
+        self.en_passant_square = if mov.is_double_pawn_push() { (from_index + to_index) as i32 / 2 } else { -1 };
+
+        if moving_piece == Piece::Pawn_W || moving_piece == Piece::Pawn_B || mov.is_capture() {
+            self.move_rep_count = 0;
+        } else {
+            self.move_rep_count += 1;
+        }
+
+        self.white_turn = !self.white_turn;
+        self.board_hash ^= self.zobrist_hash_table[65][0] ^ self.hash_en_passant();
+        self.move_count += 1;
+
+        undo
+    }
+
+    // passes the turn for null move pruning, the side to move must not be in check
+    pub fn make_null_move(&mut self) -> UndoInfo {
+        let undo = UndoInfo {
+            mov: Move::NULL,
+            captured_piece: Piece::Empty,
+            castle_status: self.castle_status,
+            en_passant_square: self.en_passant_square,
+            move_rep_count: self.move_rep_count,
+            board_hash: self.board_hash
+        };
+
+        self.board_hash ^= self.hash_en_passant();
+        self.en_passant_square = -1;
+        self.white_turn = !self.white_turn;
+        self.board_hash ^= self.zobrist_hash_table[65][0];
+        self.move_count += 1;
+
+        undo
+    }
+
+    pub fn unmake_null_move(&mut self, undo: UndoInfo) {
+        self.white_turn = !self.white_turn;
+        self.move_count -= 1;
+        self.en_passant_square = undo.en_passant_square;
+        self.board_hash = undo.board_hash;
+    }
+
+    pub fn unmake_move(&mut self, undo: UndoInfo) {
+        let mov = undo.mov;
+        let from_index = mov.from();
+        let to_index = mov.to();
+
+        self.white_turn = !self.white_turn;
+        self.move_count -= 1;
+
+        if mov.is_castle() {
+            let (rook_from, rook_to) = Engine::castle_rook_squares(mov);
+            let rook = self.board[rook_to];
+            self.set_square(rook_to, Piece::Empty);
+            self.set_square(rook_from, rook);
+        }
+
+        let moved_piece = if mov.is_promotion() {
+            if self.white_turn { Piece::Pawn_W } else { Piece::Pawn_B }
+        } else {
+            self.board[to_index]
+        };
+        self.set_square(to_index, Piece::Empty);
+        self.set_square(from_index, moved_piece);
+
+        if undo.captured_piece != Piece::Empty {
+            let captured_index = if !mov.is_en_passant() {
+                to_index
+            } else if self.white_turn {
+                to_index + 8
+            } else {
+                to_index - 8
+            };
+            self.set_square(captured_index, undo.captured_piece);
+        }
+
+        self.castle_status = undo.castle_status;
+        self.en_passant_square = undo.en_passant_square;
+        self.move_rep_count = undo.move_rep_count;
+        self.board_hash = undo.board_hash;
+    }
+
+    pub fn legal_moves(&mut self) -> Vec<Move> {
+        self.get_all_valid_moves(false, self.get_attacked_squares(self.white_turn))
+    }
+
+    pub fn board_hash(&self) -> u64 {
+        self.board_hash
+    }
+
+    // perft split by root move, in move generation order
+    pub fn perft_divide(&mut self, depth: i32, bulk_count: bool) -> Vec<PerftEntry> {
+        if depth <= 0 {
+            return vec![];
+        }
+
+        let valid_moves = self.get_all_valid_moves(
+            false,
+            self.get_attacked_squares(self.white_turn)
+        );
+        valid_moves.iter().map(|mov| PerftEntry {
+            mov: *mov,
+            nodes: self.perft_move(*mov, depth, bulk_count)
+        }).collect()
+    }
+
+    // plays a move from valid_moves() and finishes the turn
+    pub fn play_move(&mut self, mov: Move) {
+        self.play_game_move(mov);
+    }
+}
@@ -0,0 +1,632 @@
+use std::fmt;
+use std::io::BufRead;
+
+use wasm_bindgen::prelude::*;
+
+use crate::Engine;
+use crate::defs::Move;
+use crate::fen::{FenError, STARTPOS_FEN};
+use crate::game::{Game, NodeId};
+use crate::san::SanError;
+
+// the tags every exported game starts with, in export order
+pub const SEVEN_TAG_ROSTER: [&str; 7] = ["Event", "Site", "Date", "Round", "White", "Black", "Result"];
+
+const MAX_LINE_LENGTH: usize = 79;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PgnError {
+    InvalidTag(String),
+    UnterminatedComment,
+    UnbalancedVariation,
+    VariationWithoutMove,
+    Fen(FenError),
+    Move(SanError),
+    Io(String),
+}
+
+impl fmt::Display for PgnError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PgnError::InvalidTag(tag) => write!(f, "invalid tag pair '{}'", tag),
+            PgnError::UnterminatedComment => write!(f, "comment is never closed"),
+            PgnError::UnbalancedVariation => write!(f, "unbalanced variation parentheses"),
+            PgnError::VariationWithoutMove => write!(f, "variation does not follow a move"),
+            PgnError::Fen(error) => write!(f, "invalid FEN tag: {}", error),
+            PgnError::Move(error) => write!(f, "{}", error),
+            PgnError::Io(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for PgnError {}
+
+impl From<FenError> for PgnError {
+    fn from(error: FenError) -> PgnError {
+        PgnError::Fen(error)
+    }
+}
+
+// thrown as an Error on the js side
+impl From<PgnError> for JsValue {
+    fn from(error: PgnError) -> JsValue {
+        JsError::new(&error.to_string()).into()
+    }
+}
+
+impl From<SanError> for PgnError {
+    fn from(error: SanError) -> PgnError {
+        PgnError::Move(error)
+    }
+}
+
+// a sequence of moves, either the mainline or a variation
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PgnLine {
+    pub comment: Option<String>, // before the first move
+    pub moves: Vec<PgnMove>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PgnMove {
+    pub mov: Move,
+    pub san: String,
+    pub nags: Vec<u8>,
+    pub comment: Option<String>,
+    pub variations: Vec<PgnLine>, // alternatives to this move
+}
+
+impl PgnMove {
+    pub fn new(mov: Move, san: String) -> PgnMove {
+        PgnMove {
+            mov,
+            san,
+            nags: vec![],
+            comment: None,
+            variations: vec![],
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PgnGame {
+    pub tags: Vec<(String, String)>,
+    pub mainline: PgnLine,
+    pub result: String,
+}
+
+impl Default for PgnGame {
+    fn default() -> Self {
+        PgnGame {
+            tags: vec![],
+            mainline: PgnLine::default(),
+            result: String::from("*"),
+        }
+    }
+}
+
+impl PgnGame {
+    // a game played from start_fen, moves must be legal in sequence
+    pub fn from_moves(start_fen: &str, moves: &[Move]) -> Result<PgnGame, PgnError> {
+        let mut engine = Engine::new();
+        engine.parse_fen(start_fen)?;
+
+        let mut game = PgnGame::default();
+        if engine.to_fen() != STARTPOS_FEN {
+            game.set_tag("SetUp", "1");
+            game.set_tag("FEN", &engine.to_fen());
+        }
+
+        for mov in moves.iter() {
+            if !engine.legal_moves().contains(mov) {
+                let notation = format!("{}{}", Engine::index_to_notation(mov.from()), Engine::index_to_notation(mov.to()));
+                return Err(PgnError::Move(SanError::IllegalMove(notation)));
+            }
+            let san = engine.move_to_san(*mov);
+            engine.make_move(*mov);
+            game.mainline.moves.push(PgnMove::new(*mov, san));
+        }
+
+        Ok(game)
+    }
+
+    // the whole tree of a game, sidelines become recursive variations
+    pub fn from_game(game: &Game) -> Result<PgnGame, PgnError> {
+        let mut engine = Engine::new();
+        engine.parse_fen(game.start_fen())?;
+
+        let mut pgn = PgnGame {
+            tags: game.tags.clone(),
+            result: game.result.clone(),
+            ..Default::default()
+        };
+        if engine.to_fen() != STARTPOS_FEN {
+            pgn.set_tag("SetUp", "1");
+            pgn.set_tag("FEN", &engine.to_fen());
+        }
+        pgn.mainline = match game.node(Game::ROOT).children().first() {
+            Some(first) => game_line(&mut engine, game, *first),
+            None => PgnLine::default()
+        };
+        pgn.mainline.comment = join_comments(&game.node(Game::ROOT).comment, &pgn.mainline.comment);
+
+        // a game played rather than imported only knows its result from the final position of the mainline
+        if pgn.result == "*" {
+            engine.parse_fen(game.start_fen())?;
+            let mut id = Game::ROOT;
+            while let Some(child) = game.node(id).children().first() {
+                engine.play_move(game.node(*child).mov);
+                id = *child;
+            }
+            let status = engine.game_status();
+            if status.is_over() {
+                pgn.result = String::from(status.result());
+            }
+        }
+
+        Ok(pgn)
+    }
+
+    // parses the first game in the text
+    pub fn parse(pgn: &str) -> Result<PgnGame, PgnError> {
+        parse_game(&mut Engine::new(), pgn)
+    }
+
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags.iter().find(|(n, _)| n == name).map(|(_, v)| v.as_str())
+    }
+
+    pub fn set_tag(&mut self, name: &str, value: &str) {
+        match self.tags.iter_mut().find(|(n, _)| n == name) {
+            Some(tag) => tag.1 = String::from(value),
+            None => self.tags.push((String::from(name), String::from(value)))
+        }
+    }
+
+    pub fn start_fen(&self) -> &str {
+        self.tag("FEN").unwrap_or(STARTPOS_FEN)
+    }
+
+    pub fn mainline_moves(&self) -> Vec<Move> {
+        self.mainline.moves.iter().map(|m| m.mov).collect()
+    }
+
+    pub fn to_pgn(&self) -> String {
+        let mut pgn = String::new();
+
+        for name in SEVEN_TAG_ROSTER.iter() {
+            let value = match *name {
+                "Result" => self.result.as_str(),
+                "Date" => self.tag(name).unwrap_or("????.??.??"),
+                _ => self.tag(name).unwrap_or("?")
+            };
+            write_tag(&mut pgn, name, value);
+        }
+        for (name, value) in self.tags.iter().filter(|(n, _)| !SEVEN_TAG_ROSTER.contains(&n.as_str())) {
+            write_tag(&mut pgn, name, value);
+        }
+        pgn.push('\n');
+
+        let mut tokens = vec![];
+        write_line(&mut tokens, &self.mainline, start_ply(self.start_fen()));
+        tokens.push(self.result.clone());
+
+        // wrap the movetext, tokens are never split
+        let mut line_length = 0;
+        for token in tokens.iter() {
+            if line_length > 0 && line_length + 1 + token.len() > MAX_LINE_LENGTH {
+                pgn.push('\n');
+                line_length = 0;
+            } else if line_length > 0 {
+                pgn.push(' ');
+                line_length += 1;
+            }
+            pgn += token;
+            line_length += token.len();
+        }
+        pgn.push('\n');
+
+        pgn
+    }
+}
+
+// reads games one at a time from a multi game pgn source
+pub struct PgnReader<R: BufRead> {
+    reader: R,
+    engine: Engine,
+    next_line: Option<String>,
+}
+
+impl<R: BufRead> PgnReader<R> {
+    pub fn new(reader: R) -> PgnReader<R> {
+        PgnReader {
+            reader,
+            engine: Engine::new(),
+            next_line: None,
+        }
+    }
+
+    // the raw text of the next game, split after a result or where a tag section follows movetext
+    fn read_game_text(&mut self) -> Result<Option<String>, PgnError> {
+        let mut text = String::new();
+        let mut seen_movetext = false;
+        let mut in_comment = false;
+
+        loop {
+            let line = match self.next_line.take() {
+                Some(line) => line,
+                None => {
+                    let mut line = String::new();
+                    match self.reader.read_line(&mut line) {
+                        Ok(0) => break,
+                        Ok(_) => line,
+                        Err(e) => return Err(PgnError::Io(e.to_string()))
+                    }
+                }
+            };
+
+            let trimmed = line.trim();
+            if !in_comment && trimmed.starts_with('[') && seen_movetext {
+                self.next_line = Some(line);
+                break;
+            }
+            if !in_comment && !trimmed.is_empty() && !trimmed.starts_with('[') && !trimmed.starts_with('%') {
+                seen_movetext = true;
+            }
+            for c in line.chars() {
+                match c {
+                    '{' => in_comment = true,
+                    '}' => in_comment = false,
+                    _ => {}
+                }
+            }
+            text += &line;
+
+            // a result token ends the game even without another tag section
+            let trimmed = line.trim_end();
+            if !in_comment && seen_movetext && ["1-0", "0-1", "1/2-1/2", "*"].iter().any(|r| trimmed.ends_with(r)) {
+                break;
+            }
+        }
+
+        if text.trim().is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(text))
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for PgnReader<R> {
+    type Item = Result<PgnGame, PgnError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.read_game_text() {
+            Ok(Some(text)) => Some(parse_game(&mut self.engine, &text)),
+            Ok(None) => None,
+            Err(e) => Some(Err(e))
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Tag(String, String),
+    Comment(String),
+    Nag(u8),
+    Symbol(String),
+    Result(String),
+    VariationStart,
+    VariationEnd,
+}
+
+// suffix annotations map to the first six nags
+fn suffix_nag(suffix: &str) -> Option<u8> {
+    match suffix {
+        "!" => Some(1),
+        "?" => Some(2),
+        "!!" => Some(3),
+        "??" => Some(4),
+        "!?" => Some(5),
+        "?!" => Some(6),
+        _ => None
+    }
+}
+
+fn is_symbol_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "+#=:-/_".contains(c)
+}
+
+fn tokenize(pgn: &str) -> Result<Vec<Token>, PgnError> {
+    let chars: Vec<char> = pgn.chars().collect();
+    let mut tokens = vec![];
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let line_start = i == 0 || chars[i - 1] == '\n';
+
+        if c.is_whitespace() {
+            i += 1;
+        } else if (c == '%' && line_start) || c == ';' { // escaped line or rest of line comment
+            let start = i + 1;
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+            if c == ';' {
+                tokens.push(Token::Comment(chars[start..i].iter().collect::<String>().trim().to_string()));
+            }
+        } else if c == '{' {
+            let start = i + 1;
+            while i < chars.len() && chars[i] != '}' {
+                i += 1;
+            }
+            if i == chars.len() {
+                return Err(PgnError::UnterminatedComment);
+            }
+            let comment: String = chars[start..i].iter().collect();
+            tokens.push(Token::Comment(comment.split_whitespace().collect::<Vec<&str>>().join(" ")));
+            i += 1;
+        } else if c == '[' {
+            let start = i;
+            let mut in_quotes = false;
+            while i < chars.len() && chars[i] != '\n' && (in_quotes || chars[i] != ']') {
+                match chars[i] {
+                    '"' => in_quotes = !in_quotes,
+                    '\\' if in_quotes => i += 1,
+                    _ => {}
+                }
+                i += 1;
+            }
+            let tag: String = chars[start..(i + 1).min(chars.len())].iter().collect();
+            tokens.push(parse_tag(&tag).ok_or(PgnError::InvalidTag(tag.trim().to_string()))?);
+            i += 1;
+        } else if c == '(' {
+            tokens.push(Token::VariationStart);
+            i += 1;
+        } else if c == ')' {
+            tokens.push(Token::VariationEnd);
+            i += 1;
+        } else if c == '*' {
+            tokens.push(Token::Result(String::from("*")));
+            i += 1;
+        } else if c == '$' {
+            let start = i + 1;
+            i += 1;
+            while i < chars.len() && chars[i].is_ascii_digit() {
+                i += 1;
+            }
+            let nag: String = chars[start..i].iter().collect();
+            tokens.push(Token::Nag(nag.parse().unwrap_or_default()));
+        } else if c == '!' || c == '?' {
+            let start = i;
+            while i < chars.len() && (chars[i] == '!' || chars[i] == '?') {
+                i += 1;
+            }
+            let suffix: String = chars[start..i].iter().collect();
+            if let Some(nag) = suffix_nag(&suffix) {
+                tokens.push(Token::Nag(nag));
+            }
+        } else if is_symbol_char(c) {
+            let start = i;
+            while i < chars.len() && is_symbol_char(chars[i]) {
+                i += 1;
+            }
+            let symbol: String = chars[start..i].iter().collect();
+
+            if symbol.chars().all(|c| c.is_ascii_digit()) { // move number indicator
+                while i < chars.len() && chars[i] == '.' {
+                    i += 1;
+                }
+            } else if ["1-0", "0-1", "1/2-1/2"].contains(&symbol.as_str()) {
+                tokens.push(Token::Result(symbol));
+            } else {
+                tokens.push(Token::Symbol(symbol));
+            }
+        } else { // stray periods and anything unknown
+            i += 1;
+        }
+    }
+
+    Ok(tokens)
+}
+
+fn parse_tag(tag: &str) -> Option<Token> {
+    let inner = tag.trim().strip_prefix('[')?.strip_suffix(']')?.trim();
+    let (name, value) = inner.split_once(char::is_whitespace)?;
+    let value = value.trim().strip_prefix('"')?.strip_suffix('"')?;
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return None;
+    }
+
+    Some(Token::Tag(String::from(name), value.replace("\\\"", "\"").replace("\\\\", "\\")))
+}
+
+fn parse_game(engine: &mut Engine, pgn: &str) -> Result<PgnGame, PgnError> {
+    let tokens = tokenize(pgn)?;
+    let mut game = PgnGame::default();
+
+    let mut index = 0;
+    while let Some(Token::Tag(name, value)) = tokens.get(index) {
+        game.set_tag(name, value);
+        index += 1;
+    }
+
+    engine.parse_fen(game.start_fen())?;
+    game.mainline = parse_line(engine, &tokens, &mut index, false)?;
+
+    game.result = match tokens.get(index) {
+        Some(Token::Result(result)) => result.clone(),
+        _ => String::from(game.tag("Result").unwrap_or("*"))
+    };
+
+    Ok(game)
+}
+
+// reads moves until the end of the variation or game, leaving the engine where it started
+fn parse_line(engine: &mut Engine, tokens: &[Token], index: &mut usize, variation: bool) -> Result<PgnLine, PgnError> {
+    let mut line = PgnLine::default();
+    let mut undos = vec![];
+
+    while let Some(token) = tokens.get(*index) {
+        match token {
+            Token::Symbol(san) => {
+                let mov = engine.move_from_san(san)?;
+                let san = engine.move_to_san(mov);
+                undos.push(engine.make_move(mov));
+                line.moves.push(PgnMove::new(mov, san));
+            },
+            Token::Comment(comment) => {
+                let target = match line.moves.last_mut() {
+                    Some(last) => &mut last.comment,
+                    None => &mut line.comment
+                };
+                match target {
+                    Some(existing) => { existing.push(' '); existing.push_str(comment); },
+                    None => *target = Some(comment.clone())
+                }
+            },
+            Token::Nag(nag) => {
+                if let Some(last) = line.moves.last_mut() {
+                    last.nags.push(*nag);
+                }
+            },
+            Token::VariationStart => {
+                let undo = undos.pop().ok_or(PgnError::VariationWithoutMove)?;
+                engine.unmake_move(undo);
+
+                *index += 1;
+                let sideline = parse_line(engine, tokens, index, true)?;
+
+                let last = line.moves.last_mut().unwrap();
+                undos.push(engine.make_move(last.mov));
+                if !sideline.moves.is_empty() {
+                    last.variations.push(sideline);
+                }
+            },
+            Token::VariationEnd => {
+                if !variation {
+                    return Err(PgnError::UnbalancedVariation);
+                }
+                break;
+            },
+            Token::Result(_) if !variation => break,
+            Token::Result(_) | Token::Tag(_, _) => {}
+        }
+        *index += 1;
+    }
+
+    if variation && tokens.get(*index) != Some(&Token::VariationEnd) {
+        return Err(PgnError::UnbalancedVariation);
+    }
+
+    while let Some(undo) = undos.pop() {
+        engine.unmake_move(undo);
+    }
+
+    Ok(line)
+}
+
+fn join_comments(first: &Option<String>, second: &Option<String>) -> Option<String> {
+    match (first, second) {
+        (Some(a), Some(b)) => Some(format!("{} {}", a, b)),
+        (a, b) => a.clone().or_else(|| b.clone())
+    }
+}
+
+// the line starting at first and following the mainline, leaving the engine where it started
+fn game_line(engine: &mut Engine, game: &Game, first: NodeId) -> PgnLine {
+    let mut line = PgnLine {
+        comment: game.node(first).comment_before.clone(),
+        moves: vec![],
+    };
+    let mut undos = vec![];
+
+    let mut id = Some(first);
+    while let Some(node_id) = id {
+        let node = game.node(node_id);
+        let mut mov = PgnMove::new(node.mov, engine.move_to_san(node.mov));
+        mov.nags = node.nags.clone();
+        mov.comment = node.comment.clone();
+
+        // a comment before a mainline move reads the same after the previous move
+        if node_id != first {
+            if let Some(previous) = line.moves.last_mut() {
+                previous.comment = join_comments(&previous.comment, &node.comment_before);
+            }
+        }
+
+        // siblings are alternatives to this move, written after it when it is the main choice
+        let siblings = game.node(node.parent().unwrap()).children();
+        if siblings.first() == Some(&node_id) {
+            for sibling in siblings.iter().skip(1) {
+                mov.variations.push(game_line(engine, game, *sibling));
+            }
+        }
+
+        undos.push(engine.make_move(node.mov));
+        line.moves.push(mov);
+        id = node.children().first().copied();
+    }
+
+    while let Some(undo) = undos.pop() {
+        engine.unmake_move(undo);
+    }
+
+    line
+}
+
+fn write_tag(pgn: &mut String, name: &str, value: &str) {
+    *pgn += &format!("[{} \"{}\"]\n", name, value.replace('\\', "\\\\").replace('"', "\\\""));
+}
+
+// plies since the start of the game, from the fen side to move and fullmove fields
+fn start_ply(fen: &str) -> usize {
+    let fields: Vec<&str> = fen.split_whitespace().collect();
+    let fullmove: usize = fields.get(5).and_then(|f| f.parse().ok()).unwrap_or(1).max(1);
+    (fullmove - 1) * 2 + (fields.get(1) == Some(&"b")) as usize
+}
+
+fn write_comment(tokens: &mut Vec<String>, comment: &str) {
+    let mut words: Vec<String> = comment.split_whitespace().map(String::from).collect();
+    if words.is_empty() {
+        words.push(String::new());
+    }
+    words[0].insert(0, '{');
+    words.last_mut().unwrap().push('}');
+    tokens.append(&mut words);
+}
+
+fn write_line(tokens: &mut Vec<String>, line: &PgnLine, start_ply: usize) {
+    if let Some(comment) = &line.comment {
+        write_comment(tokens, comment);
+    }
+
+    // black moves need their number at the start of a line or after an interruption
+    let mut needs_number = true;
+    for (i, mov) in line.moves.iter().enumerate() {
+        let ply = start_ply + i;
+        if ply.is_multiple_of(2) {
+            tokens.push(format!("{}.", ply / 2 + 1));
+        } else if needs_number {
+            tokens.push(format!("{}...", ply / 2 + 1));
+        }
+        tokens.push(mov.san.clone());
+        needs_number = false;
+
+        for nag in mov.nags.iter() {
+            tokens.push(format!("${}", nag));
+        }
+        if let Some(comment) = &mov.comment {
+            write_comment(tokens, comment);
+            needs_number = true;
+        }
+        for variation in mov.variations.iter() {
+            let start = tokens.len();
+            write_line(tokens, variation, ply);
+            tokens[start].insert(0, '(');
+            tokens.last_mut().unwrap().push(')');
+            needs_number = true;
+        }
+    }
+}
@@ -0,0 +1,158 @@
+use crate::defs::Move;
+
+// data reported at the end of every completed search iteration
+#[derive(Clone)]
+pub struct SearchInfo {
+    pub depth: i32,
+    pub score: i32, // relative to the side to move
+    pub white_turn: bool,
+    pub time: u32, // ms since the search started
+    pub best_move: Move,
+    pub pv: Vec<Move>, // starts with best_move
+    pub pv_san: String, // the pv as numbered san
+    pub multipv: usize, // rank of the line, 1 for the best
+    pub nodes: u64, // searched so far
+}
+
+// milliseconds from an arbitrary starting point; only differences are used
+pub trait Clock: Send {
+    fn now(&self) -> u32;
+}
+
+pub trait LogSink: Send {
+    fn log(&self, s: &str);
+}
+
+pub trait InfoSink: Send {
+    fn post_info(&mut self, info: &SearchInfo);
+}
+
+// sinks which discard everything
+pub struct NullLogSink;
+impl LogSink for NullLogSink {
+    fn log(&self, _s: &str) {}
+}
+
+pub struct NullInfoSink;
+impl InfoSink for NullInfoSink {
+    fn post_info(&mut self, _info: &SearchInfo) {}
+}
+
+#[cfg(target_arch = "wasm32")]
+mod wasm {
+    use wasm_bindgen::prelude::*;
+    use super::{Clock, InfoSink, LogSink, SearchInfo};
+    use crate::defs::mate_in;
+
+    #[wasm_bindgen]
+    extern "C" {
+        #[wasm_bindgen(js_namespace = console)]
+        fn log(s: &str);
+
+        // workaround to perf.now() in webworkers
+        type Date;
+        #[wasm_bindgen(static_method_of = Date)]
+        fn now() -> u32;
+
+        #[wasm_bindgen(js_namespace = self)]
+        fn post_eval_message(s: String, eval: i32, pv: String);
+    }
+
+    pub struct WasmClock;
+    impl Clock for WasmClock {
+        fn now(&self) -> u32 {
+            Date::now()
+        }
+    }
+
+    pub struct ConsoleLogSink;
+    impl LogSink for ConsoleLogSink {
+        fn log(&self, s: &str) {
+            log(s);
+        }
+    }
+
+    // update eval on frontend if this is being run in the eval worker
+    // mates are sent as text such as M3 or -M3, from white's point of view like the score
+    pub struct EvalMessageInfoSink;
+    impl InfoSink for EvalMessageInfoSink {
+        fn post_info(&mut self, info: &SearchInfo) {
+            if info.multipv != 1 {
+                return;
+            }
+
+            let mate = match mate_in(info.score) {
+                Some(moves) => format!("{}M{}", if (moves > 0) == info.white_turn { "" } else { "-" }, moves.abs()),
+                None => String::new()
+            };
+            post_eval_message(mate, if info.white_turn { info.score } else { -info.score }, info.pv_san.clone());
+        }
+    }
+
+    pub fn default_clock() -> Box<dyn Clock> {
+        Box::new(WasmClock)
+    }
+
+    pub fn default_log_sink() -> Box<dyn LogSink> {
+        Box::new(ConsoleLogSink)
+    }
+
+    pub fn default_info_sink() -> Box<dyn InfoSink> {
+        Box::new(EvalMessageInfoSink)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod native {
+    use std::time::Instant;
+    use super::{Clock, InfoSink, LogSink, NullInfoSink};
+
+    pub struct InstantClock {
+        start: Instant
+    }
+
+    impl InstantClock {
+        pub fn new() -> InstantClock {
+            InstantClock {
+                start: Instant::now()
+            }
+        }
+    }
+
+    impl Default for InstantClock {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl Clock for InstantClock {
+        fn now(&self) -> u32 {
+            self.start.elapsed().as_millis() as u32
+        }
+    }
+
+    pub struct StderrLogSink;
+    impl LogSink for StderrLogSink {
+        fn log(&self, s: &str) {
+            eprintln!("{}", s);
+        }
+    }
+
+    pub fn default_clock() -> Box<dyn Clock> {
+        Box::new(InstantClock::new())
+    }
+
+    pub fn default_log_sink() -> Box<dyn LogSink> {
+        Box::new(StderrLogSink)
+    }
+
+    pub fn default_info_sink() -> Box<dyn InfoSink> {
+        Box::new(NullInfoSink)
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+pub use wasm::*;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use native::*;
@@ -0,0 +1,93 @@
+use wasm_bindgen::prelude::*;
+
+use crate::defs::{EvalMove, Move, ScoredMove, mate_in};
+
+// deeper than any search can reach, lines are cut off past it
+pub const MAX_PLY: usize = 128;
+
+// triangular table of principal variations, row ply holds the best line found from that ply onwards
+pub struct PvTable {
+    moves: Vec<[Move; MAX_PLY]>,
+    ends: [usize; MAX_PLY], // one past the last move of each row
+}
+
+impl Default for PvTable {
+    fn default() -> Self {
+        PvTable::new()
+    }
+}
+
+impl PvTable {
+    pub fn new() -> PvTable {
+        PvTable {
+            moves: vec![[Move::NULL; MAX_PLY]; MAX_PLY],
+            ends: [0; MAX_PLY],
+        }
+    }
+
+    // called when a node is entered, before anything can be returned from it
+    pub fn clear(&mut self, ply: usize) {
+        if ply < MAX_PLY {
+            self.ends[ply] = ply;
+        }
+    }
+
+    // mov became the best move at ply, so the line continues with the best line below it
+    pub fn update(&mut self, ply: usize, mov: Move) {
+        if ply >= MAX_PLY {
+            return;
+        }
+
+        self.moves[ply][ply] = mov;
+        let end = if ply + 1 < MAX_PLY { self.ends[ply + 1].max(ply + 1) } else { MAX_PLY };
+        for i in ply + 1..end {
+            self.moves[ply][i] = self.moves[ply + 1][i];
+        }
+        self.ends[ply] = end;
+    }
+
+    pub fn line(&self) -> &[Move] {
+        &self.moves[0][..self.ends[0]]
+    }
+}
+
+// one ranked line of a search, multipv searches report several
+#[wasm_bindgen]
+#[derive(Clone)]
+pub struct PvLine {
+    pub mov: EvalMove, // the first move, scored with the line's score
+    pub score: i32, // relative to the side to move
+    pub depth: i32,
+    #[wasm_bindgen(skip)]
+    pub moves: Vec<Move>,
+    #[wasm_bindgen(skip)]
+    pub san: String,
+}
+
+#[wasm_bindgen]
+impl PvLine {
+    pub fn mate_in(&self) -> Option<i32> {
+        mate_in(self.score)
+    }
+
+    // numbered san, e.g. "1. e4 e5 2. Nf3"
+    pub fn san(&self) -> String {
+        self.san.clone()
+    }
+
+    pub fn uci(&self) -> String {
+        self.moves.iter().map(|m| m.to_uci()).collect::<Vec<String>>().join(" ")
+    }
+}
+
+impl PvLine {
+    pub fn new(best: ScoredMove, depth: i32, moves: Vec<Move>, san: String) -> PvLine {
+        PvLine {
+            mov: EvalMove::new(best.mov, best.score),
+            score: best.score,
+            depth,
+            moves,
+            san,
+        }
+    }
+}
@@ -0,0 +1,214 @@
+use std::fmt;
+
+use wasm_bindgen::prelude::*;
+
+use crate::Engine;
+use crate::defs::{Move, MoveFlag, Piece};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SanError {
+    InvalidSyntax(String),
+    IllegalMove(String),
+    AmbiguousMove(String),
+}
+
+impl fmt::Display for SanError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SanError::InvalidSyntax(san) => write!(f, "'{}' is not a valid move", san),
+            SanError::IllegalMove(san) => write!(f, "'{}' is not legal in this position", san),
+            SanError::AmbiguousMove(san) => write!(f, "'{}' matches more than one legal move", san),
+        }
+    }
+}
+
+impl std::error::Error for SanError {}
+
+impl From<SanError> for JsValue {
+    fn from(error: SanError) -> JsValue {
+        JsError::new(&error.to_string()).into()
+    }
+}
+
+// the black piece of the same type, so both colors compare equal
+fn piece_type(piece: Piece) -> Piece {
+    if piece >= Piece::King_W { Piece::from_num(piece as i32 - 6) } else { piece }
+}
+
+fn piece_letter(piece: Piece) -> Option<char> {
+    match piece_type(piece) {
+        Piece::King_B => Some('K'),
+        Piece::Queen_B => Some('Q'),
+        Piece::Rook_B => Some('R'),
+        Piece::Bishop_B => Some('B'),
+        Piece::Knight_B => Some('N'),
+        _ => None
+    }
+}
+
+fn letter_piece(letter: char) -> Option<Piece> {
+    match letter {
+        'K' => Some(Piece::King_B),
+        'Q' => Some(Piece::Queen_B),
+        'R' => Some(Piece::Rook_B),
+        'B' => Some(Piece::Bishop_B),
+        'N' => Some(Piece::Knight_B),
+        _ => None
+    }
+}
+
+fn file_char(index: usize) -> char {
+    (b'a' + (index % 8) as u8) as char
+}
+
+fn rank_char(index: usize) -> char {
+    (b'8' - (index / 8) as u8) as char
+}
+
+impl Engine {
+    // the move must be legal in the current position
+    pub fn move_to_san(&mut self, mov: Move) -> String {
+        let mut san = String::new();
+        let moving_piece = self.board[mov.from()];
+
+        if mov.is_castle() {
+            san += if mov.flags() == MoveFlag::KING_CASTLE { "O-O" } else { "O-O-O" };
+        } else {
+            match piece_letter(moving_piece) {
+                Some(letter) => {
+                    san.push(letter);
+
+                    // other pieces of the same type which can reach the same square
+                    let others: Vec<Move> = self.legal_moves()
+                        .into_iter()
+                        .filter(|m| m.to() == mov.to() && m.from() != mov.from() && self.board[m.from()] == moving_piece)
+                        .collect();
+                    if !others.is_empty() {
+                        if others.iter().all(|m| m.from() % 8 != mov.from() % 8) {
+                            san.push(file_char(mov.from()));
+                        } else if others.iter().all(|m| m.from() / 8 != mov.from() / 8) {
+                            san.push(rank_char(mov.from()));
+                        } else {
+                            san.push(file_char(mov.from()));
+                            san.push(rank_char(mov.from()));
+                        }
+                    }
+                },
+                None => if mov.is_capture() {
+                    san.push(file_char(mov.from()));
+                }
+            }
+
+            if mov.is_capture() {
+                san.push('x');
+            }
+            san.push(file_char(mov.to()));
+            san.push(rank_char(mov.to()));
+
+            if let Some(letter) = piece_letter(mov.promotion_piece()) {
+                san.push('=');
+                san.push(letter);
+            }
+        }
+
+        let undo = self.make_move(mov);
+        if self.is_in_check(self.white_turn) {
+            san.push(if self.legal_moves().is_empty() { '#' } else { '+' });
+        }
+        self.unmake_move(undo);
+
+        san
+    }
+
+    // accepts check and annotation suffixes, 0-0 castling and promotions without '='
+    pub fn move_from_san(&mut self, san: &str) -> Result<Move, SanError> {
+        let invalid = || SanError::InvalidSyntax(String::from(san));
+        let trimmed = san.trim().trim_end_matches(|c| "+#!?".contains(c));
+        let legal_moves = self.legal_moves();
+
+        let castle_flag = match trimmed {
+            "O-O" | "0-0" => Some(MoveFlag::KING_CASTLE),
+            "O-O-O" | "0-0-0" => Some(MoveFlag::QUEEN_CASTLE),
+            _ => None
+        };
+        if let Some(flag) = castle_flag {
+            return legal_moves.into_iter()
+                .find(|m| m.flags() == flag)
+                .ok_or_else(|| SanError::IllegalMove(String::from(san)));
+        }
+
+        let mut chars: Vec<char> = trimmed.chars().collect();
+
+        // promotion suffix
+        let mut promotion = None;
+        if chars.len() > 2 {
+            if let Some(piece) = letter_piece(chars[chars.len() - 1]) {
+                promotion = Some(piece);
+                chars.pop();
+                if chars.last() == Some(&'=') {
+                    chars.pop();
+                }
+            }
+        }
+
+        // destination square
+        if chars.len() < 2 {
+            return Err(invalid());
+        }
+        let to_rank = chars.pop().unwrap();
+        let to_file = chars.pop().unwrap();
+        if !('a'..='h').contains(&to_file) || !('1'..='8').contains(&to_rank) {
+            return Err(invalid());
+        }
+        let to_index = Engine::notation_to_index(to_rank, to_file);
+
+        // piece letter and disambiguation
+        let mut piece = Piece::Pawn_B;
+        let mut from_file = None;
+        let mut from_rank = None;
+        for (i, c) in chars.iter().enumerate() {
+            match c {
+                'K' | 'Q' | 'R' | 'B' | 'N' if i == 0 => piece = letter_piece(*c).unwrap(),
+                'a'..='h' => from_file = Some(*c as usize - 'a' as usize),
+                '1'..='8' => from_rank = Some('8' as usize - *c as usize),
+                'x' | ':' | '-' => {},
+                _ => return Err(invalid())
+            }
+        }
+
+        let matches: Vec<Move> = legal_moves.into_iter()
+            .filter(|m| m.to() == to_index && !m.is_castle())
+            .filter(|m| piece_type(self.board[m.from()]) == piece)
+            .filter(|m| from_file.is_none_or(|file| m.from() % 8 == file))
+            .filter(|m| from_rank.is_none_or(|rank| m.from() / 8 == rank))
+            .filter(|m| promotion.map_or(!m.is_promotion(), |p| m.is_promotion() && piece_type(m.promotion_piece()) == p))
+            .collect();
+
+        match matches.len() {
+            0 => Err(SanError::IllegalMove(String::from(san))),
+            1 => Ok(matches[0]),
+            _ => Err(SanError::AmbiguousMove(String::from(san)))
+        }
+    }
+
+    // a line of moves from the current position, numbered like "12... Nf6 13. Bg5"
+    pub fn line_to_san(&mut self, moves: &[Move]) -> String {
+        let mut tokens = vec![];
+        let mut undos = vec![];
+        for mov in moves.iter() {
+            let ply = self.move_count as usize;
+            if ply.is_multiple_of(2) {
+                tokens.push(format!("{}.", ply / 2 + 1));
+            } else if undos.is_empty() {
+                tokens.push(format!("{}...", ply / 2 + 1));
+            }
+            tokens.push(self.move_to_san(*mov));
+            undos.push(self.make_move(*mov));
+        }
+        while let Some(undo) = undos.pop() {
+            self.unmake_move(undo);
+        }
+
+        tokens.join(" ")
+    }
+}
@@ -0,0 +1,117 @@
+use std::fmt;
+
+use crate::Engine;
+use crate::bitboard::Bitboard;
+use crate::defs::Piece;
+
+// squares of the same colour as a8
+const LIGHT_SQUARES: Bitboard = 0xAA55_AA55_AA55_AA55;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    White,
+    Black,
+}
+
+// how the game on the board stands, following the fide rules
+// fifty move and threefold draws have to be claimed, every other ending is automatic
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameStatus {
+    Ongoing,
+    Checkmate(Side), // the winner
+    Stalemate,
+    InsufficientMaterial,
+    FiftyMove,
+    Threefold,
+    SeventyFiveMove,
+    Fivefold,
+}
+
+impl GameStatus {
+    pub fn is_over(self) -> bool {
+        !matches!(self, GameStatus::Ongoing | GameStatus::FiftyMove | GameStatus::Threefold)
+    }
+
+    pub fn is_claimable_draw(self) -> bool {
+        matches!(self, GameStatus::FiftyMove | GameStatus::Threefold)
+    }
+
+    pub fn is_draw(self) -> bool {
+        !matches!(self, GameStatus::Ongoing | GameStatus::Checkmate(_))
+    }
+
+    // the pgn result token, a claimable draw counts as drawn
+    pub fn result(self) -> &'static str {
+        match self {
+            GameStatus::Ongoing => "*",
+            GameStatus::Checkmate(Side::White) => "1-0",
+            GameStatus::Checkmate(Side::Black) => "0-1",
+            _ => "1/2-1/2"
+        }
+    }
+}
+
+impl fmt::Display for GameStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GameStatus::Ongoing => write!(f, "Ongoing"),
+            GameStatus::Checkmate(Side::White) => write!(f, "White mates"),
+            GameStatus::Checkmate(Side::Black) => write!(f, "Black mates"),
+            GameStatus::Stalemate => write!(f, "Stalemate"),
+            GameStatus::InsufficientMaterial => write!(f, "Insufficient material"),
+            GameStatus::FiftyMove => write!(f, "Fifty move rule"),
+            GameStatus::Threefold => write!(f, "Threefold repetition"),
+            GameStatus::SeventyFiveMove => write!(f, "Seventy-five move rule"),
+            GameStatus::Fivefold => write!(f, "Fivefold repetition"),
+        }
+    }
+}
+
+impl Engine {
+    // checkmate takes priority over the move counters, as a mating move ends the game on its own
+    pub fn game_status(&self) -> GameStatus {
+        if self.all_valid_moves.is_empty() {
+            return match (self.in_check, self.white_turn) {
+                (false, _) => GameStatus::Stalemate,
+                (true, true) => GameStatus::Checkmate(Side::Black),
+                (true, false) => GameStatus::Checkmate(Side::White)
+            };
+        }
+
+        let repetitions = self.repetition_count();
+        if self.is_insufficient_material() {
+            GameStatus::InsufficientMaterial
+        } else if repetitions >= 5 {
+            GameStatus::Fivefold
+        } else if self.move_rep_count >= 150 {
+            GameStatus::SeventyFiveMove
+        } else if repetitions >= 3 {
+            GameStatus::Threefold
+        } else if self.move_rep_count >= 100 {
+            GameStatus::FiftyMove
+        } else {
+            GameStatus::Ongoing
+        }
+    }
+
+    // how often the current position has occurred since the last pawn move or capture
+    pub fn repetition_count(&self) -> usize {
+        self.repetition_history.iter().filter(|h| **h == self.board_hash).count()
+    }
+
+    // neither side can mate: KvK, KNvK, KBvK, or only bishops that all stand on one square colour
+    pub fn is_insufficient_material(&self) -> bool {
+        let count = |pieces: &[Piece]| pieces.iter().map(|p| self.bitboards[*p as usize].count_ones()).sum::<u32>();
+        if count(&[Piece::Queen_W, Piece::Queen_B, Piece::Rook_W, Piece::Rook_B, Piece::Pawn_W, Piece::Pawn_B]) > 0 {
+            return false;
+        }
+
+        let knights = count(&[Piece::Knight_W, Piece::Knight_B]);
+        let bishops = self.bitboards[Piece::Bishop_W as usize] | self.bitboards[Piece::Bishop_B as usize];
+        if knights + bishops.count_ones() <= 1 {
+            return true;
+        }
+
+        knights == 0 && (bishops & LIGHT_SQUARES == 0 || bishops & !LIGHT_SQUARES == 0)
+    }
+}
@@ -0,0 +1,215 @@
+use crate::defs::Move;
+
+// all times are in ms
+
+// kept back from every budget to cover move transmission and timer granularity
+const MOVE_OVERHEAD: u32 = 50;
+// moves assumed to be left in the game when the time control does not say
+const DEFAULT_MOVES_TO_GO: u32 = 30;
+// a fall in score between iterations, in centipawns, that is worth extra time
+const SCORE_DROP: i32 = 50;
+// iterations the best move has to survive before the search may stop early
+const STABLE_ITERATIONS: u32 = 3;
+// lead in centipawns over the second best move which makes the best one clear
+pub const CLEAR_MARGIN: i32 = 150;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeControl {
+    SuddenDeath { base: u32 },
+    Fischer { base: u32, increment: u32 }, // increment added after every move
+    Bronstein { base: u32, delay: u32 }, // time used is given back, up to the delay
+    SimpleDelay { base: u32, delay: u32 }, // the clock only starts once the delay has passed
+    MovesToGo { base: u32, moves: u32 }, // base is added again after every period of moves
+}
+
+impl TimeControl {
+    pub fn base(self) -> u32 {
+        match self {
+            TimeControl::SuddenDeath { base } |
+            TimeControl::Fischer { base, .. } |
+            TimeControl::Bronstein { base, .. } |
+            TimeControl::SimpleDelay { base, .. } |
+            TimeControl::MovesToGo { base, .. } => base
+        }
+    }
+
+    // time a move can be expected to get back, or not be charged for
+    fn bonus(self) -> u32 {
+        match self {
+            TimeControl::Fischer { increment, .. } => increment,
+            TimeControl::Bronstein { delay, .. } | TimeControl::SimpleDelay { delay, .. } => delay,
+            _ => 0
+        }
+    }
+}
+
+// how long a single search may take
+// the soft limit is the target, a new iteration is not started after it, while the hard limit aborts the search
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchBudget {
+    pub soft: u32,
+    pub hard: u32,
+}
+
+impl SearchBudget {
+    pub fn fixed(time: u32) -> SearchBudget {
+        SearchBudget { soft: time, hard: time }
+    }
+}
+
+// splits the remaining clock over the moves left, bonus is the increment or delay each move gets
+pub fn allocate(remaining: u32, bonus: u32, moves_to_go: Option<u32>) -> SearchBudget {
+    let moves_to_go = moves_to_go.unwrap_or(DEFAULT_MOVES_TO_GO).max(1);
+    let safe = remaining.saturating_sub(MOVE_OVERHEAD).max(1);
+
+    let soft = (remaining / moves_to_go + bonus * 3 / 4).clamp(1, safe);
+    let hard = soft.saturating_mul(4).min(safe / 2).max(soft);
+    SearchBudget { soft, hard }
+}
+
+// decides after each iteration of a search whether another one is worth starting
+// unstable searches get up to the hard limit, a stable or clearly best move gives back half of the soft limit and one that is both three quarters
+pub struct TimeManager {
+    budget: SearchBudget,
+    target: u32,
+    best_move: Move,
+    score: Option<i32>,
+    stable_iterations: u32,
+    iteration_time: Option<u32>,
+}
+
+impl TimeManager {
+    pub fn new(budget: SearchBudget) -> TimeManager {
+        TimeManager {
+            budget,
+            target: budget.soft,
+            best_move: Move::NULL,
+            score: None,
+            stable_iterations: 0,
+            iteration_time: None,
+        }
+    }
+
+    // the soft limit as adjusted by the last iteration
+    pub fn target(&self) -> u32 {
+        self.target
+    }
+
+    // best_move_changes is how often the root best move was replaced during the iteration, starting from the last iteration's
+    // margin is the best score less the second best, none when it could not be found
+    pub fn should_continue(&mut self, best_move: Move, score: i32, best_move_changes: usize, margin: Option<i32>, elapsed: u32, iteration_time: u32) -> bool {
+        let changed = self.score.is_some() && best_move != self.best_move;
+        let dropped = self.score.is_some_and(|s| s.saturating_sub(score) >= SCORE_DROP);
+        self.stable_iterations = if changed || best_move_changes > 1 { 0 } else { self.stable_iterations + 1 };
+        let stable = self.stable_iterations >= STABLE_ITERATIONS;
+        let clear = margin.is_some_and(|m| m >= CLEAR_MARGIN);
+
+        let percent: u64 = if dropped {
+            200
+        } else if changed {
+            150
+        } else if stable && clear {
+            25
+        } else if stable || clear {
+            50
+        } else {
+            100
+        };
+        self.target = (self.budget.soft as u64 * percent / 100).min(self.budget.hard as u64) as u32;
+
+        // each depth costs a multiple of the last, judged from how the last two grew
+        let growth = match self.iteration_time {
+            Some(previous) if previous > 0 => (iteration_time / previous).clamp(2, 8),
+            _ => 3
+        };
+        let next_iteration = iteration_time.saturating_mul(growth);
+
+        self.best_move = best_move;
+        self.score = Some(score);
+        self.iteration_time = Some(iteration_time);
+
+        elapsed < self.target && elapsed.saturating_add(next_iteration) <= self.budget.hard
+    }
+}
+
+// the clocks of both sides during a game
+#[derive(Debug, Clone)]
+pub struct ChessClock {
+    control: TimeControl,
+    remaining: [u32; 2], // white, black
+    moves: [u32; 2],
+}
+
+impl ChessClock {
+    pub fn new(control: TimeControl) -> ChessClock {
+        ChessClock {
+            control,
+            remaining: [control.base(); 2],
+            moves: [0; 2],
+        }
+    }
+
+    pub fn control(&self) -> TimeControl {
+        self.control
+    }
+
+    pub fn remaining(&self, white: bool) -> u32 {
+        self.remaining[side(white)]
+    }
+
+    // for syncing with an external clock, such as a gui or server
+    pub fn set_remaining(&mut self, white: bool, remaining: u32) {
+        self.remaining[side(white)] = remaining;
+    }
+
+    pub fn is_flagged(&self, white: bool) -> bool {
+        self.remaining[side(white)] == 0
+    }
+
+    pub fn moves_to_go(&self, white: bool) -> Option<u32> {
+        match self.control {
+            TimeControl::MovesToGo { moves, .. } if moves > 0 => Some(moves - self.moves[side(white)] % moves),
+            _ => None
+        }
+    }
+
+    // charges a finished move to the side that made it, a flagged side stays at zero
+    pub fn record_move(&mut self, white: bool, elapsed: u32) {
+        let side = side(white);
+        if self.remaining[side] == 0 {
+            return;
+        }
+
+        let charged = match self.control {
+            TimeControl::SimpleDelay { delay, .. } => elapsed.saturating_sub(delay),
+            _ => elapsed
+        };
+        if charged >= self.remaining[side] {
+            self.remaining[side] = 0;
+            return;
+        }
+
+        self.remaining[side] -= charged;
+        self.moves[side] += 1;
+        self.remaining[side] += match self.control {
+            TimeControl::Fischer { increment, .. } => increment,
+            TimeControl::Bronstein { delay, .. } => elapsed.min(delay),
+            TimeControl::MovesToGo { base, moves } if moves > 0 && self.moves[side].is_multiple_of(moves) => base,
+            _ => 0
+        };
+    }
+
+    pub fn allocate(&self, white: bool) -> SearchBudget {
+        let remaining = self.remaining(white);
+        let budget = allocate(remaining, self.control.bonus(), self.moves_to_go(white));
+        match self.control {
+            // the delay is not charged, so the hard limit can run into it
+            TimeControl::SimpleDelay { delay, .. } => SearchBudget { hard: budget.hard + delay, ..budget },
+            _ => budget
+        }
+    }
+}
+
+fn side(white: bool) -> usize {
+    if white { 0 } else { 1 }
+}
@@ -0,0 +1,157 @@
+use std::mem::size_of;
+
+use crate::defs::{LARGEST_SAFE_I32, MAX_MATE_PLY, Move, SMALLEST_SAFE_I32, SavedEvalType};
+
+pub const DEFAULT_HASH_SIZE: usize = 16; // MB
+const BUCKET_SIZE: usize = 4;
+const AGE_MASK: u8 = 0x3F; // the low 6 bits of age_bound, the top 2 hold the bound
+
+// one search result, 12 bytes
+#[derive(Clone, Copy, Default)]
+pub struct TtEntry {
+    key: u32, // the top half of the hash, the bottom half picks the bucket
+    score: i32,
+    mov: Move,
+    depth: u8,
+    age_bound: u8, // age 0 marks an unused slot
+}
+
+impl TtEntry {
+    pub fn score(&self) -> i32 {
+        self.score
+    }
+
+    // null when no move improved alpha
+    pub fn mov(&self) -> Move {
+        self.mov
+    }
+
+    pub fn depth(&self) -> i32 {
+        self.depth as i32
+    }
+
+    pub fn bound(&self) -> SavedEvalType {
+        match self.age_bound >> 6 {
+            0 => SavedEvalType::Exact,
+            1 => SavedEvalType::Alpha,
+            _ => SavedEvalType::Beta
+        }
+    }
+
+    fn age(&self) -> u8 {
+        self.age_bound & AGE_MASK
+    }
+
+    fn is_empty(&self) -> bool {
+        self.age() == 0
+    }
+}
+
+type Bucket = [TtEntry; BUCKET_SIZE];
+
+// a preallocated hash table of search results which is kept from one search to the next
+// entries from older searches and shallower depths are overwritten first
+pub struct TranspositionTable {
+    buckets: Vec<Bucket>,
+    age: u8,
+}
+
+impl TranspositionTable {
+    pub fn new(megabytes: usize) -> TranspositionTable {
+        let count = (megabytes * 1024 * 1024 / size_of::<Bucket>()).max(1);
+        TranspositionTable {
+            buckets: vec![Bucket::default(); count],
+            age: 1,
+        }
+    }
+
+    // in MB, rounded to the nearest
+    pub fn size(&self) -> usize {
+        (self.buckets.len() * size_of::<Bucket>() + 512 * 1024) / (1024 * 1024)
+    }
+
+    pub fn clear(&mut self) {
+        self.buckets.fill(Bucket::default());
+        self.age = 1;
+    }
+
+    // called once per search so entries from earlier searches can be told apart
+    pub fn new_search(&mut self) {
+        self.age = self.age % AGE_MASK + 1;
+    }
+
+    // ply is the distance of the position from the root, which mate scores are given back relative to
+    pub fn probe(&self, hash: u64, ply: i32) -> Option<TtEntry> {
+        let key = (hash >> 32) as u32;
+        let entry = self.buckets[self.index(hash)].iter().find(|e| !e.is_empty() && e.key == key)?;
+        Some(TtEntry { score: score_from_tt(entry.score, ply), ..*entry })
+    }
+
+    pub fn store(&mut self, hash: u64, mov: Move, score: i32, depth: i32, bound: SavedEvalType, ply: i32) {
+        let score = score_to_tt(score, ply);
+        let key = (hash >> 32) as u32;
+        let age = self.age;
+        let index = self.index(hash);
+        let bucket = &mut self.buckets[index];
+
+        // the same position, otherwise the least valuable slot
+        let slot = match bucket.iter().position(|e| !e.is_empty() && e.key == key) {
+            Some(slot) => slot,
+            None => (0..BUCKET_SIZE).min_by_key(|i| {
+                let entry = &bucket[*i];
+                if entry.is_empty() {
+                    i32::MIN
+                } else {
+                    entry.depth() - 4 * ((age + AGE_MASK - entry.age()) % AGE_MASK) as i32
+                }
+            }).unwrap()
+        };
+
+        let old = bucket[slot];
+        let same_position = !old.is_empty() && old.key == key;
+        if same_position && old.age() == age && bound != SavedEvalType::Exact && depth < old.depth() {
+            return; // a deeper result from this search is worth more than a bound
+        }
+
+        bucket[slot] = TtEntry {
+            key,
+            score,
+            mov: if mov.is_null() && same_position { old.mov } else { mov },
+            depth: depth.clamp(0, u8::MAX as i32) as u8,
+            age_bound: (bound as u8) << 6 | age,
+        };
+    }
+
+    // per mille of the sampled slots used by the current search, as uci reports it
+    pub fn hash_full(&self) -> u32 {
+        let sample = self.buckets.iter().take(1000 / BUCKET_SIZE).flatten();
+        let used = sample.clone().filter(|e| !e.is_empty() && e.age() == self.age).count();
+        (used * 1000 / sample.count().max(1)) as u32
+    }
+
+    fn index(&self, hash: u64) -> usize {
+        (hash as u32 as usize) % self.buckets.len()
+    }
+}
+
+// mates are stored as a distance from the entry's position rather than the root,
+// so they stay right when the position is reached again at another ply
+fn score_to_tt(score: i32, ply: i32) -> i32 {
+    if score >= LARGEST_SAFE_I32 - MAX_MATE_PLY {
+        score + ply
+    } else if score <= SMALLEST_SAFE_I32 + MAX_MATE_PLY {
+        score - ply
+    } else {
+        score
+    }
+}
+
+fn score_from_tt(score: i32, ply: i32) -> i32 {
+    if score >= LARGEST_SAFE_I32 - MAX_MATE_PLY {
+        score - ply
+    } else if score <= SMALLEST_SAFE_I32 + MAX_MATE_PLY {
+        score + ply
+    } else {
+        score
+    }
+}
@@ -0,0 +1,87 @@
+use std::fmt;
+
+use wasm_bindgen::prelude::*;
+
+use crate::Engine;
+use crate::defs::{Move, Piece};
+
+// long algebraic moves as used by uci, e.g. e2e4 or e7e8n
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UciError {
+    InvalidSyntax(String),
+    IllegalMove(String),
+    MissingPromotion(String),
+}
+
+impl fmt::Display for UciError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            UciError::InvalidSyntax(uci) => write!(f, "'{}' is not a valid move", uci),
+            UciError::IllegalMove(uci) => write!(f, "'{}' is not legal in this position", uci),
+            UciError::MissingPromotion(uci) => write!(f, "'{}' promotes but does not name a piece", uci),
+        }
+    }
+}
+
+impl std::error::Error for UciError {}
+
+impl From<UciError> for JsValue {
+    fn from(error: UciError) -> JsValue {
+        JsError::new(&error.to_string()).into()
+    }
+}
+
+fn promotion_char(piece: Piece) -> Option<char> {
+    match piece {
+        Piece::Queen_W | Piece::Queen_B => Some('q'),
+        Piece::Rook_W | Piece::Rook_B => Some('r'),
+        Piece::Bishop_W | Piece::Bishop_B => Some('b'),
+        Piece::Knight_W | Piece::Knight_B => Some('n'),
+        _ => None
+    }
+}
+
+fn parse_square(file: char, rank: char) -> Option<usize> {
+    if ('a'..='h').contains(&file) && ('1'..='8').contains(&rank) {
+        Some(Engine::notation_to_index(rank, file))
+    } else {
+        None
+    }
+}
+
+impl Move {
+    pub fn to_uci(self) -> String {
+        let mut uci = Engine::index_to_notation(self.from()) + &Engine::index_to_notation(self.to());
+        if let Some(c) = promotion_char(self.promotion_piece()) {
+            uci.push(c);
+        }
+        uci
+    }
+}
+
+impl Engine {
+    // the legal move a uci string refers to, case insensitive
+    pub fn parse_uci_move(&mut self, uci: &str) -> Result<Move, UciError> {
+        let lowercase = uci.trim().to_ascii_lowercase();
+        let chars: Vec<char> = lowercase.chars().collect();
+        let invalid = || UciError::InvalidSyntax(String::from(uci));
+
+        if chars.len() != 4 && chars.len() != 5 {
+            return Err(invalid());
+        }
+        let from = parse_square(chars[0], chars[1]).ok_or_else(invalid)?;
+        let to = parse_square(chars[2], chars[3]).ok_or_else(invalid)?;
+        let promotion = chars.get(4).copied();
+        if promotion.is_some_and(|c| !"qrbn".contains(c)) {
+            return Err(invalid());
+        }
+
+        let candidates: Vec<Move> = self.legal_moves().into_iter().filter(|m| m.from() == from && m.to() == to).collect();
+        if promotion.is_none() && candidates.iter().any(|m| m.is_promotion()) {
+            return Err(UciError::MissingPromotion(String::from(uci)));
+        }
+        candidates.into_iter()
+            .find(|m| promotion_char(m.promotion_piece()) == promotion)
+            .ok_or_else(|| UciError::IllegalMove(String::from(uci)))
+    }
+}
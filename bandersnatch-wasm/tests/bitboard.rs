@@ -0,0 +1,59 @@
+use bandersnatch_wasm::bitboard::{AttackTables, Bitboard, has_square, square_bb};
+
+const ROOK_DIRECTIONS: [(i32, i32); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+const BISHOP_DIRECTIONS: [(i32, i32); 4] = [(1, 1), (-1, 1), (1, -1), (-1, -1)];
+
+// walks each ray until it leaves the board or hits a blocker
+fn ray_attacks(index: usize, occupancy: Bitboard, directions: &[(i32, i32)]) -> Bitboard {
+    let mut attacks = 0;
+    for &(dx, dy) in directions {
+        let (mut x, mut y) = ((index % 8) as i32 + dx, (index / 8) as i32 + dy);
+        while (0..8).contains(&x) && (0..8).contains(&y) {
+            let square = (y * 8 + x) as usize;
+            attacks |= square_bb(square);
+            if has_square(occupancy, square) {
+                break;
+            }
+            x += dx;
+            y += dy;
+        }
+    }
+    attacks
+}
+
+// xorshift, so every run checks the same boards
+fn random_boards(count: usize) -> Vec<Bitboard> {
+    let mut state: u64 = 0x2545_F491_4F6C_DD1D;
+    let mut next = || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    };
+    (0..count).map(|_| next() & next()).collect()
+}
+
+#[test]
+fn sliders_match_the_ray_walk() {
+    let tables = AttackTables::get();
+    for occupancy in random_boards(500).into_iter().chain([0, u64::MAX]) {
+        for index in 0..64 {
+            let rook = ray_attacks(index, occupancy, &ROOK_DIRECTIONS);
+            let bishop = ray_attacks(index, occupancy, &BISHOP_DIRECTIONS);
+            assert_eq!(tables.rook(index, occupancy), rook, "rook on {} with {:#x}", index, occupancy);
+            assert_eq!(tables.bishop(index, occupancy), bishop, "bishop on {} with {:#x}", index, occupancy);
+            assert_eq!(tables.queen(index, occupancy), rook | bishop, "queen on {} with {:#x}", index, occupancy);
+        }
+    }
+}
+
+#[test]
+fn blockers_on_the_edge_do_not_matter() {
+    let tables = AttackTables::get();
+    // rook on d4 (index 35) with pieces on d1, d8, a4 and h4
+    let edges = square_bb(59) | square_bb(3) | square_bb(32) | square_bb(39);
+    assert_eq!(tables.rook(35, edges), tables.rook(35, 0));
+    // a piece on d6 cuts the ray towards d8
+    let blocked = tables.rook(35, square_bb(19));
+    assert!(has_square(blocked, 19) && !has_square(blocked, 11) && !has_square(blocked, 3));
+}
@@ -0,0 +1,20 @@
+// helpers shared by the test files, each of which only uses some of them
+#![allow(dead_code)]
+
+use std::sync::{Arc, Mutex};
+
+use bandersnatch_wasm::{Engine, platform::{InfoSink, SearchInfo}};
+
+pub fn from_fen(fen: &str) -> Engine {
+    let mut engine = Engine::new();
+    engine.parse_fen(fen).unwrap();
+    engine
+}
+
+// keeps every report of a search for the test to inspect
+pub struct CollectingSink(pub Arc<Mutex<Vec<SearchInfo>>>);
+impl InfoSink for CollectingSink {
+    fn post_info(&mut self, info: &SearchInfo) {
+        self.0.lock().unwrap().push(info.clone());
+    }
+}
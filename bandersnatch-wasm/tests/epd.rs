@@ -0,0 +1,80 @@
+use bandersnatch_wasm::{Engine, epd::{EpdError, SearchLimit}, san::SanError};
+
+#[test]
+fn parses_operations() {
+    let mut engine = Engine::new();
+    let position = engine.parse_epd(
+        "2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 w - - bm Qg6; id \"WAC.001\"; c0 \"mate; in two\"; hmvc 3; fmvn 20;"
+    ).unwrap();
+
+    assert_eq!(position.id.as_deref(), Some("WAC.001"));
+    assert_eq!(position.c0.as_deref(), Some("mate; in two"));
+    assert_eq!(position.hmvc, Some(3));
+    assert_eq!(position.fmvn, Some(20));
+    assert_eq!(position.fen, "2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 w - - 3 20");
+    assert_eq!(position.bm, vec![engine.move_from_san("Qg6").unwrap()]);
+    assert_eq!(position.operations.len(), 5);
+    assert_eq!(engine.to_fen(), position.fen);
+}
+
+#[test]
+fn parses_move_lists_and_mates() {
+    let mut engine = Engine::new();
+    let position = engine.parse_epd("4k3/8/8/8/8/8/8/R3K2R w KQ - am O-O Kd1; bm Ra8+ Rh8+; dm 1; unknown 1 2").unwrap();
+    assert_eq!(position.am.len(), 2);
+    assert_eq!(position.bm.len(), 2);
+    assert_eq!(position.dm, Some(1));
+    assert_eq!(position.fen, "4k3/8/8/8/8/8/8/R3K2R w KQ - 0 1");
+    assert_eq!(position.operations[3], (String::from("unknown"), vec![String::from("1"), String::from("2")]));
+
+    assert!(position.is_solved_by(position.bm[1], Some(1)));
+    assert!(!position.is_solved_by(position.am[0], Some(1)));
+    // the best move alone is not enough when the mate was not seen
+    assert!(!position.is_solved_by(position.bm[1], None));
+    assert!(!position.is_solved_by(position.bm[1], Some(2)));
+}
+
+#[test]
+fn rejects_bad_epd() {
+    let mut engine = Engine::new();
+    assert_eq!(engine.parse_epd("4k3/8/8/8/8/8/8/4K3 w -").err(), Some(EpdError::FieldCount(3)));
+    assert_eq!(engine.parse_epd("4k3/8/8/8/8/8/8/4K3 w - - dm x;").err(), Some(EpdError::InvalidOperation(String::from("dm x"))));
+    assert_eq!(engine.parse_epd("4k3/8/8/8/8/8/8/4K3 w - - id \"open;").err(), Some(EpdError::InvalidOperation(String::from("id \"open;"))));
+    assert_eq!(engine.parse_epd("4k3/8/8/8/8/8/8/4K3 w - - bm Qh5;").err(), Some(EpdError::Move(SanError::IllegalMove(String::from("Qh5")))));
+    assert!(matches!(engine.parse_epd("4k3/8/8/8/8/8/8/8 w - - bm Kd1;"), Err(EpdError::Fen(_))));
+}
+
+#[test]
+fn runner_counts_solved_positions() {
+    let mut engine = Engine::new();
+    let positions = [
+        engine.parse_epd("6k1/5ppp/8/8/8/8/8/R5K1 w - - bm Ra8#; id \"back rank\";").unwrap(),
+        engine.parse_epd("6k1/5ppp/8/8/8/8/8/R5K1 w - - am Ra8#; id \"avoid mate\";").unwrap(),
+        engine.parse_epd("k7/8/1K6/8/8/8/7Q/8 w - - bm Qh8# Qa2+; id \"first of two\";").unwrap(),
+    ];
+
+    let report = engine.run_epd(&positions, SearchLimit::Depth(2));
+    assert_eq!(report.results.len(), 3);
+    assert_eq!(report.solved(), 2);
+    assert_eq!(report.results[0].id.as_deref(), Some("back rank"));
+    assert_eq!(report.results[0].san, "Ra8#");
+    assert!(report.results[0].solved);
+    assert!(!report.results[1].solved);
+    assert!(report.results.iter().all(|r| r.depth >= 1));
+}
+
+#[test]
+fn runner_checks_direct_mates() {
+    let mut engine = Engine::new();
+    let positions = [
+        engine.parse_epd("6k1/5ppp/8/8/8/8/8/R5K1 w - - dm 1; id \"back rank\";").unwrap(),
+        // mate in two is not found by a two ply search
+        engine.parse_epd("k7/8/2K5/8/8/8/8/1R6 w - - dm 2; id \"too deep\";").unwrap(),
+    ];
+
+    let report = engine.run_epd(&positions, SearchLimit::Depth(2));
+    assert_eq!(report.results[0].mate, Some(1));
+    assert!(report.results[0].solved);
+    assert_eq!(report.results[1].mate, None);
+    assert!(!report.results[1].solved);
+}
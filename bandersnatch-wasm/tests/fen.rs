@@ -0,0 +1,71 @@
+use bandersnatch_wasm::{Engine, fen::{FenError, STARTPOS_FEN}};
+
+fn parse(fen: &str) -> Result<Engine, FenError> {
+    let mut engine = Engine::new();
+    engine.parse_fen(fen)?;
+    Ok(engine)
+}
+
+#[test]
+fn round_trip() {
+    for fen in [
+        STARTPOS_FEN,
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+        "8/8/8/8/8/8/k6p/7K b - - 12 61",
+    ] {
+        assert_eq!(parse(fen).unwrap().to_fen(), fen);
+    }
+}
+
+#[test]
+fn move_counters_are_optional() {
+    let engine = parse("4k3/8/8/8/8/8/8/4K3 b - -").unwrap();
+    assert_eq!(engine.to_fen(), "4k3/8/8/8/8/8/8/4K3 b - - 0 1");
+}
+
+#[test]
+fn fen_follows_played_moves() {
+    let mut engine = parse(STARTPOS_FEN).unwrap();
+    for (from, to) in [(52, 36), (11, 27), (36, 28), (13, 29)] { // e4 d5 e5 f5
+        let mov = *engine.valid_moves().iter().find(|m| m.from() == from && m.to() == to).unwrap();
+        engine.play_move(mov);
+    }
+    assert_eq!(engine.to_fen(), "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3");
+}
+
+#[test]
+fn rejects_malformed_fields() {
+    assert_eq!(parse("8/8/8/8 w - - 0").err(), Some(FenError::FieldCount(5)));
+    assert_eq!(parse("4k3/8/8/8/8/8/4K3 w - - 0 1").err(), Some(FenError::RankCount(7)));
+    assert_eq!(parse("4k3/8/8/9/8/8/8/4K3 w - - 0 1").err(), Some(FenError::RankLength { rank: 5, length: 9 }));
+    assert_eq!(parse("4k3/8/8/ppp/8/8/8/4K3 w - - 0 1").err(), Some(FenError::RankLength { rank: 5, length: 3 }));
+    assert_eq!(parse("4k3/8/8/8/3x4/8/8/4K3 w - - 0 1").err(), Some(FenError::InvalidPiece('x')));
+    assert_eq!(parse("4k3/8/8/8/8/8/8/4K3 x - - 0 1").err(), Some(FenError::InvalidSideToMove(String::from("x"))));
+    assert_eq!(parse("4k3/8/8/8/8/8/8/4K2R w KK - 0 1").err(), Some(FenError::InvalidCastling(String::from("KK"))));
+    assert_eq!(parse("4k3/8/8/8/8/8/8/4K3 w - e9 0 1").err(), Some(FenError::InvalidEnPassant(String::from("e9"))));
+    assert_eq!(parse("4k3/8/8/8/8/8/8/4K3 w - - x 1").err(), Some(FenError::InvalidMoveCounter(String::from("x"))));
+    assert_eq!(parse("4k3/8/8/8/8/8/8/4K3 w - - 0 0").err(), Some(FenError::InvalidMoveCounter(String::from("0"))));
+}
+
+#[test]
+fn rejects_illegal_positions() {
+    assert_eq!(parse("8/8/8/8/8/8/8/4K3 w - - 0 1").err(), Some(FenError::MissingKing { white: false }));
+    assert_eq!(parse("4k3/8/8/8/8/8/8/3KK3 w - - 0 1").err(), Some(FenError::TooManyKings { white: true }));
+    assert_eq!(parse("P3k3/8/8/8/8/8/8/4K3 w - - 0 1").err(), Some(FenError::PawnOnBackRank(String::from("a8"))));
+    assert_eq!(parse("4k3/8/8/8/8/8/8/4K3 w K - 0 1").err(), Some(FenError::CastlingRightsMismatch('K')));
+    assert_eq!(parse("4k2r/8/8/8/8/8/8/4K3 w q - 0 1").err(), Some(FenError::CastlingRightsMismatch('q')));
+    assert_eq!(parse("4k3/8/8/8/8/8/8/4K3 w - e6 0 1").err(), Some(FenError::ImpossibleEnPassant(String::from("e6"))));
+    assert_eq!(parse("4k3/8/8/4p3/8/8/8/4K3 b - e6 0 1").err(), Some(FenError::ImpossibleEnPassant(String::from("e6"))));
+    assert_eq!(parse("4k3/8/8/8/8/8/8/4K2R w - - 0 1").err(), None);
+    assert_eq!(parse("4k2R/8/8/8/8/8/8/4K3 w - - 0 1").err(), Some(FenError::OpponentInCheck));
+}
+
+#[test]
+fn rejected_fen_leaves_engine_unchanged() {
+    let mut engine = parse(STARTPOS_FEN).unwrap();
+    assert!(engine.parse_fen("4k2R/8/8/8/8/8/8/4K3 w - - 0 1").is_err());
+    assert_eq!(engine.to_fen(), STARTPOS_FEN);
+    assert_eq!(engine.valid_moves().len(), 20);
+}
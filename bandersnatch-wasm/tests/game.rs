@@ -0,0 +1,103 @@
+use bandersnatch_wasm::{Engine, defs::Piece, fen::STARTPOS_FEN};
+
+fn play(engine: &mut Engine, moves: &[&str]) {
+    for san in moves {
+        let mov = engine.move_from_san(san).unwrap();
+        engine.play_move(mov);
+    }
+}
+
+fn fen_after(moves: &[&str]) -> String {
+    let mut engine = Engine::new();
+    engine.parse_fen(STARTPOS_FEN).unwrap();
+    play(&mut engine, moves);
+    engine.to_fen()
+}
+
+#[test]
+fn undo_and_redo_walk_the_line() {
+    let mut engine = Engine::new();
+    engine.parse_fen(STARTPOS_FEN).unwrap();
+    play(&mut engine, &["e4", "d5", "exd5", "Qxd5", "Nc3"]);
+    let end = engine.to_fen();
+
+    assert!(engine.undo_move());
+    assert!(engine.undo_move());
+    assert_eq!(engine.to_fen(), fen_after(&["e4", "d5", "exd5"]));
+    assert_eq!(engine.game_ply(), 3);
+    assert_eq!(engine.game_length(), 5);
+    assert_eq!(engine.valid_moves().len(), engine.legal_moves().len());
+    assert!(engine.piece_captured_this_turn());
+
+    assert!(engine.redo_move());
+    assert!(engine.redo_move());
+    assert!(!engine.redo_move());
+    assert_eq!(engine.to_fen(), end);
+    assert_eq!(engine.board_hash(), engine.hash_board());
+}
+
+#[test]
+fn goto_ply_jumps_anywhere() {
+    let mut engine = Engine::new();
+    engine.parse_fen(STARTPOS_FEN).unwrap();
+    play(&mut engine, &["e4", "e5", "Nf3", "Nc6", "Bb5"]);
+
+    assert!(engine.goto_ply(0));
+    assert_eq!(engine.to_fen(), STARTPOS_FEN);
+    assert!(!engine.undo_move());
+
+    assert!(engine.goto_ply(4));
+    assert_eq!(engine.to_fen(), fen_after(&["e4", "e5", "Nf3", "Nc6"]));
+    assert!(!engine.goto_ply(6));
+    assert_eq!(engine.game_ply(), 4);
+}
+
+#[test]
+fn playing_a_new_move_branches() {
+    let mut engine = Engine::new();
+    engine.parse_fen(STARTPOS_FEN).unwrap();
+    play(&mut engine, &["e4", "e5", "Nf3"]);
+
+    // replaying the next move keeps the rest of the line
+    engine.goto_ply(1);
+    play(&mut engine, &["e5"]);
+    assert_eq!(engine.game_length(), 3);
+
+    // a different move starts a sideline
+    play(&mut engine, &["Bc4"]);
+    assert_eq!(engine.game_length(), 3);
+    assert!(!engine.redo_move());
+    assert_eq!(engine.to_fen(), fen_after(&["e4", "e5", "Bc4"]));
+
+    engine.undo_move();
+    engine.truncate_game();
+    assert_eq!(engine.game_length(), 2);
+    assert_eq!(engine.game().played_moves().len(), 2);
+}
+
+#[test]
+fn human_moves_are_recorded() {
+    let mut engine = Engine::new();
+    engine.parse_fen("4k3/P7/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+    assert_eq!(engine.attempt_move(8, 0, Piece::Rook_W), Ok(true));
+    assert_eq!(engine.game().start_fen(), "4k3/P7/8/8/8/8/8/4K3 w - - 0 1");
+    assert_eq!(engine.game().moves()[0].promotion_piece(), Piece::Rook_W);
+
+    engine.undo_move();
+    assert_eq!(engine.to_fen(), "4k3/P7/8/8/8/8/8/4K3 w - - 0 1");
+}
+
+#[test]
+fn repetitions_follow_the_history() {
+    let mut engine = Engine::new();
+    engine.parse_fen(STARTPOS_FEN).unwrap();
+    play(&mut engine, &["Nf3", "Nf6", "Ng1", "Ng8", "Nf3", "Nf6", "Ng1", "Ng8"]);
+    assert!(engine.check_for_draw());
+
+    engine.undo_move();
+    engine.undo_move();
+    assert!(!engine.check_for_draw());
+
+    engine.goto_ply(8);
+    assert!(engine.check_for_draw());
+}
@@ -0,0 +1,123 @@
+use bandersnatch_wasm::{Engine, fen::STARTPOS_FEN, game::Game};
+
+const ANALYSIS: &str = r#"[Event "Analysis"]
+[Site "?"]
+[Date "????.??.??"]
+[Round "?"]
+[White "?"]
+[Black "?"]
+[Result "*"]
+
+{Open games} 1. e4 e5 (1... c5 {Sicilian} 2. Nf3 (2. c3 $5) 2... d6) (1... e6)
+2. Nf3 $1 Nc6 *
+"#;
+
+fn play(engine: &mut Engine, moves: &[&str]) {
+    for san in moves {
+        let mov = engine.move_from_san(san).unwrap();
+        engine.play_move(mov);
+    }
+}
+
+fn san_line(engine: &mut Engine) -> Vec<String> {
+    let moves = engine.game().moves();
+    let ply = engine.game_ply();
+    engine.goto_ply(0);
+    let line = moves.iter().map(|m| {
+        let san = engine.move_to_san(*m);
+        engine.play_move(*m);
+        san
+    }).collect();
+    engine.goto_ply(ply);
+    line
+}
+
+#[test]
+fn sidelines_can_be_visited_and_promoted() {
+    let mut engine = Engine::new();
+    engine.parse_fen(STARTPOS_FEN).unwrap();
+    play(&mut engine, &["e4", "e5", "Nf3"]);
+    engine.goto_ply(1);
+    play(&mut engine, &["c5", "Nf3"]);
+    let sicilian = engine.current_node();
+    let fen = engine.to_fen();
+
+    assert_eq!(engine.node_children(engine.node_parent(sicilian).unwrap()).len(), 1);
+    engine.goto_node(Game::ROOT);
+    assert_eq!(san_line(&mut engine), ["e4", "e5", "Nf3"]);
+
+    assert!(engine.goto_node(sicilian));
+    assert_eq!(engine.to_fen(), fen);
+    assert_eq!(engine.board_hash(), engine.hash_board());
+
+    assert!(engine.promote_to_mainline(sicilian));
+    engine.goto_node(Game::ROOT);
+    assert_eq!(san_line(&mut engine), ["e4", "c5", "Nf3"]);
+    assert_eq!(engine.node_children(engine.game().line()[0]).len(), 2);
+}
+
+#[test]
+fn deleting_the_viewed_branch_steps_out_of_it() {
+    let mut engine = Engine::new();
+    engine.parse_fen(STARTPOS_FEN).unwrap();
+    play(&mut engine, &["e4", "e5"]);
+    engine.goto_ply(1);
+    play(&mut engine, &["c5", "Nf3"]);
+    let sicilian = engine.game().path(engine.current_node())[1];
+
+    assert!(!engine.delete_branch(Game::ROOT));
+    assert!(engine.delete_branch(sicilian));
+    assert_eq!(engine.game_ply(), 1);
+    assert!(!engine.goto_node(sicilian));
+    assert_eq!(engine.node_children(engine.current_node()).len(), 1);
+    assert!(engine.redo_move());
+    assert_eq!(engine.last_move_san(), "e5");
+}
+
+#[test]
+fn comments_and_nags_are_kept_per_node() {
+    let mut engine = Engine::new();
+    engine.parse_fen(STARTPOS_FEN).unwrap();
+    play(&mut engine, &["d4"]);
+    let node = engine.current_node();
+
+    assert!(engine.set_node_comment(node, Some(String::from("queen's pawn"))));
+    assert!(engine.set_node_nags(node, vec![1]));
+    assert_eq!(engine.node_comment(node).as_deref(), Some("queen's pawn"));
+    assert_eq!(engine.node_nags(node), [1]);
+    assert!(engine.export_pgn().unwrap().contains("1. d4 $1 {queen's pawn} *"));
+    assert!(!engine.set_node_comment(99, None));
+}
+
+#[test]
+fn variations_round_trip_through_pgn() {
+    let mut engine = Engine::new();
+    engine.import_pgn(ANALYSIS).unwrap();
+    assert_eq!(engine.game_ply(), 0);
+    assert_eq!(engine.game_length(), 4);
+    assert_eq!(engine.export_pgn().unwrap().trim_end(), ANALYSIS.trim_end());
+
+    let first = engine.game().line()[0];
+    let replies = engine.node_children(first);
+    assert_eq!(replies.len(), 3);
+    assert_eq!(engine.node_comment(replies[1]).as_deref(), Some("Sicilian"));
+
+    // promoting the sideline swaps it with the mainline in the export
+    engine.promote_to_mainline(replies[1]);
+    let pgn = engine.export_pgn().unwrap().replace('\n', " ");
+    assert!(pgn.contains("1. e4 c5 {Sicilian} (1... e5 2. Nf3 $1 Nc6) (1... e6) 2. Nf3 (2. c3 $5) 2... d6 *"));
+}
+
+#[test]
+fn played_games_export_their_result() {
+    let mut engine = Engine::new();
+    engine.parse_fen(STARTPOS_FEN).unwrap();
+    play(&mut engine, &["f3", "e5", "g4", "Qh4#"]);
+    let pgn = engine.export_pgn().unwrap();
+    assert!(pgn.contains("[Result \"0-1\"]"));
+    assert!(pgn.trim_end().ends_with("2. g4 Qh4# 0-1"));
+
+    // the result follows the mainline, not the position being viewed
+    assert!(engine.undo_move());
+    assert!(engine.export_pgn().unwrap().trim_end().ends_with("0-1"));
+}
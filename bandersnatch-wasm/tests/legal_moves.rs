@@ -0,0 +1,26 @@
+use bandersnatch_wasm::{Engine, defs::{LARGEST_SAFE_I32, SMALLEST_SAFE_I32}};
+
+// has en passant captures from the third ply on
+const POSITION_3: &str = "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1";
+
+fn quiescence(fen: &str) -> i32 {
+    let mut engine = Engine::new();
+    engine.parse_fen(fen).unwrap();
+    engine.quiescence_search(SMALLEST_SAFE_I32, LARGEST_SAFE_I32)
+}
+
+#[test]
+fn counts_en_passant_captures() {
+    // node counts from https://www.chessprogramming.org/Perft_Results
+    let mut engine = Engine::new();
+    engine.parse_fen(POSITION_3).unwrap();
+    assert_eq!(engine.calculate_all_possible_moves(4), 43238);
+}
+
+#[test]
+fn quiescence_takes_en_passant() {
+    // exd6 is the only capture, and wins the pawn that just moved
+    let with_capture = quiescence("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1");
+    let without = quiescence("4k3/8/8/3pP3/8/8/8/4K3 w - - 0 1");
+    assert!(with_capture >= without + 50, "{} vs {}", with_capture, without);
+}
@@ -0,0 +1,83 @@
+mod common;
+
+use bandersnatch_wasm::{Engine, defs::Move};
+
+use common::from_fen;
+
+const KIWIPETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+
+// the incremental hash must always match a hash computed from scratch
+fn check_tree(engine: &mut Engine, depth: i32) {
+    assert_eq!(engine.board_hash(), engine.hash_board());
+    if depth == 0 {
+        return;
+    }
+
+    for mov in engine.legal_moves() {
+        let undo = engine.make_move(mov);
+        check_tree(engine, depth - 1);
+        engine.unmake_move(undo);
+    }
+}
+
+fn find(engine: &mut Engine, from: usize, to: usize) -> Move {
+    engine.legal_moves().into_iter().find(|m| m.from() == from && m.to() == to).unwrap()
+}
+
+#[test]
+fn incremental_hash_matches_full_hash() {
+    check_tree(&mut from_fen(KIWIPETE), 3);
+    check_tree(&mut from_fen("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1"), 4);
+    check_tree(&mut from_fen("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1"), 3);
+}
+
+#[test]
+fn unmake_restores_everything() {
+    let mut engine = from_fen(KIWIPETE);
+    let hash = engine.board_hash();
+    let castle_status = engine.castle_status();
+    let moves = engine.legal_moves();
+
+    for mov in moves.iter() {
+        let undo = engine.make_move(*mov);
+        assert!(!engine.white_turn());
+        engine.unmake_move(undo);
+
+        assert!(engine.white_turn());
+        assert_eq!(engine.board_hash(), hash);
+        assert_eq!(engine.castle_status(), castle_status);
+        assert_eq!(engine.en_passant_square(), -1);
+        assert_eq!(engine.move_rep_count(), 0);
+        assert_eq!(engine.legal_moves(), moves);
+    }
+}
+
+#[test]
+fn capturing_a_rook_removes_castle_rights() {
+    // the h8 rook takes the h1 rook, so neither side can castle kingside anymore
+    let mut engine = from_fen("r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1");
+    let mov = find(&mut engine, 7, 63);
+    let undo = engine.make_move(mov);
+    assert_eq!(engine.castle_status(), 2 | 8);
+    assert_eq!(engine.board_hash(), engine.hash_board());
+
+    engine.unmake_move(undo);
+    assert_eq!(engine.castle_status(), 1 | 2 | 4 | 8);
+}
+
+#[test]
+fn halfmove_clock_and_en_passant() {
+    let mut engine = from_fen("4k3/8/8/8/8/8/4P3/4K1N1 w - - 7 30");
+    let knight_move = find(&mut engine, 62, 45);
+    let undo = engine.make_move(knight_move);
+    assert_eq!(engine.move_rep_count(), 8);
+    engine.unmake_move(undo);
+
+    let double_push = find(&mut engine, 52, 36);
+    let undo = engine.make_move(double_push);
+    assert_eq!(engine.move_rep_count(), 0);
+    assert_eq!(engine.en_passant_square(), 44);
+    engine.unmake_move(undo);
+    assert_eq!(engine.move_rep_count(), 7);
+    assert_eq!(engine.en_passant_square(), -1);
+}
@@ -0,0 +1,72 @@
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use bandersnatch_wasm::{Engine, fen::STARTPOS_FEN};
+
+use common::{CollectingSink, from_fen};
+
+fn search(fen: &str, lines: usize, depth: i32) -> Engine {
+    let mut engine = from_fen(fen);
+    engine.set_multi_pv(lines);
+    engine.update_max_search_time(u32::MAX);
+    engine.update_max_search_depth(depth);
+    engine.find_best_move_iterative();
+    engine
+}
+
+#[test]
+fn reports_distinct_lines_best_first() {
+    let engine = search(STARTPOS_FEN, 3, 3);
+    assert_eq!(engine.pv_line_count(), 3);
+
+    let lines: Vec<_> = (0..3).map(|i| engine.pv_line(i).unwrap()).collect();
+    assert_eq!(lines[0].moves[0], engine.principal_variation()[0]);
+    assert_eq!(lines[0].score, engine.best_move().score);
+    for (i, line) in lines.iter().enumerate() {
+        assert_eq!(line.depth, 3);
+        assert!(line.uci().starts_with(&line.moves[0].to_uci()));
+        assert!(line.san().starts_with("1. "));
+        assert!(lines[i + 1..].iter().all(|l| l.moves[0] != line.moves[0]));
+        assert!(lines[i + 1..].iter().all(|l| l.score <= line.score));
+    }
+    assert!(engine.pv_line(3).is_none());
+}
+
+#[test]
+fn stops_when_the_root_moves_run_out() {
+    // the white king has a2, b1 and b2
+    let engine = search("k7/8/8/8/8/8/8/K7 w - - 0 1", 5, 2);
+    assert_eq!(engine.pv_line_count(), 3);
+}
+
+#[test]
+fn captures_rank_above_quiet_moves() {
+    // the rook can take a free queen
+    let engine = search("4k3/8/8/3q4/8/8/8/3RK3 w - - 0 1", 2, 2);
+    let best = engine.pv_line(0).unwrap();
+    let second = engine.pv_line(1).unwrap();
+    assert_eq!(best.moves[0].to_uci(), "d1d5");
+    assert!(best.score > second.score);
+}
+
+#[test]
+fn line_count_can_change_between_searches() {
+    let infos = Arc::new(Mutex::new(vec![]));
+    let mut engine = Engine::new();
+    engine.set_info_sink(Box::new(CollectingSink(infos.clone())));
+    engine.parse_fen(STARTPOS_FEN).unwrap();
+    engine.update_max_search_time(u32::MAX);
+    engine.update_max_search_depth(2);
+
+    engine.set_multi_pv(2);
+    engine.find_best_move_iterative();
+    assert_eq!(engine.pv_line_count(), 2);
+    let ranks: Vec<(i32, usize)> = infos.lock().unwrap().iter().map(|i| (i.depth, i.multipv)).collect();
+    assert_eq!(ranks, [(1, 1), (1, 2), (2, 1), (2, 2)]);
+
+    engine.set_multi_pv(0);
+    assert_eq!(engine.multi_pv(), 1);
+    engine.find_best_move_iterative();
+    assert_eq!(engine.pv_line_count(), 1);
+}
@@ -0,0 +1,82 @@
+// node counts from https://www.chessprogramming.org/Perft_Results
+// depths are kept low enough to finish quickly in a debug build
+
+mod common;
+
+use common::from_fen;
+
+const STARTPOS: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+const KIWIPETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+const POSITION_3: &str = "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1";
+const POSITION_4: &str = "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1";
+const POSITION_5: &str = "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8";
+const POSITION_6: &str = "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10";
+
+fn check_counts(fen: &str, expected: &[u64]) {
+    let mut engine = from_fen(fen);
+    for (depth, nodes) in expected.iter().enumerate() {
+        assert_eq!(engine.perft(depth as i32 + 1, true), *nodes, "{} at depth {}", fen, depth + 1);
+    }
+}
+
+#[test]
+fn startpos() {
+    check_counts(STARTPOS, &[20, 400, 8902, 197281]);
+}
+
+#[test]
+fn kiwipete() {
+    check_counts(KIWIPETE, &[48, 2039, 97862]);
+}
+
+#[test]
+fn position_3() {
+    check_counts(POSITION_3, &[14, 191, 2812, 43238, 674624]);
+}
+
+#[test]
+fn position_4() {
+    check_counts(POSITION_4, &[6, 264, 9467, 422333]);
+}
+
+#[test]
+fn position_5() {
+    check_counts(POSITION_5, &[44, 1486, 62379]);
+}
+
+#[test]
+fn position_6() {
+    check_counts(POSITION_6, &[46, 2079, 89890]);
+}
+
+#[test]
+fn bulk_counting_matches_full_expansion() {
+    for fen in [STARTPOS, KIWIPETE, POSITION_3, POSITION_4, POSITION_5, POSITION_6] {
+        let mut engine = from_fen(fen);
+        assert_eq!(engine.perft(3, false), engine.perft(3, true), "{}", fen);
+    }
+}
+
+#[test]
+fn divide_sums_to_perft() {
+    let mut engine = from_fen(KIWIPETE);
+    let divide = engine.perft_divide(3, true);
+    assert_eq!(divide.len(), 48);
+    assert_eq!(divide.iter().map(|e| e.nodes).sum::<u64>(), 97862);
+
+    // every entry should match a perft from the position after that move
+    for entry in divide.iter().take(8) {
+        let mut child = from_fen(KIWIPETE);
+        child.play_move(entry.mov);
+        assert_eq!(child.perft(2, true), entry.nodes);
+    }
+}
+
+#[test]
+fn perft_restores_the_position() {
+    let mut engine = from_fen(POSITION_4);
+    engine.perft(3, false);
+    assert!(engine.white_turn());
+    assert_eq!(engine.perft(2, true), 264);
+    assert_eq!(engine.valid_moves().len(), 6);
+}
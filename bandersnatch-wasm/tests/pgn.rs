@@ -0,0 +1,129 @@
+use bandersnatch_wasm::{Engine, fen::STARTPOS_FEN, pgn::{PgnError, PgnGame, PgnReader}, san::SanError};
+
+const OPERA_GAME: &str = r#"[Event "Paris"]
+[Site "Paris FRA"]
+[Date "1858.??.??"]
+[Round "?"]
+[White "Morphy, Paul"]
+[Black "Duke Karl / Count Isouard"]
+[Result "1-0"]
+[ECO "C41"]
+
+1. e4 e5 2. Nf3 d6 3. d4 Bg4 {This is a weak move already.} 4. dxe5 Bxf3 5. Qxf3
+dxe5 6. Bc4 Nf6 7. Qb3 Qe7 8. Nc3 c6 9. Bg5 b5 10. Nxb5 cxb5 11. Bxb5+ Nbd7 12.
+O-O-O Rd8 13. Rxd7 Rxd7 14. Rd1 Qe6 15. Bxd7+ Nxd7 16. Qb8+ Nxb8 17. Rd8# 1-0
+"#;
+
+#[test]
+fn reads_tags_and_moves() {
+    let game = PgnGame::parse(OPERA_GAME).unwrap();
+    assert_eq!(game.tag("White"), Some("Morphy, Paul"));
+    assert_eq!(game.tag("ECO"), Some("C41"));
+    assert_eq!(game.tag("Missing"), None);
+    assert_eq!(game.result, "1-0");
+    assert_eq!(game.mainline.moves.len(), 33);
+    assert_eq!(game.mainline.moves[32].san, "Rd8#");
+    assert_eq!(game.mainline.moves[5].comment.as_deref(), Some("This is a weak move already."));
+
+    // the moves replay to the final position
+    let mut engine = Engine::new();
+    engine.parse_fen(game.start_fen()).unwrap();
+    for mov in game.mainline_moves() {
+        engine.play_move(mov);
+    }
+    assert_eq!(engine.to_fen(), "1n1Rkb1r/p4ppp/4q3/4p1B1/4P3/8/PPP2PPP/2K5 b k - 1 17");
+}
+
+#[test]
+fn reads_annotations_and_variations() {
+    let pgn = "{Opening comment} 1. e4 $1 e5!? (1... c5 {Sicilian} 2. Nf3 (2. c3) d6; rest of line\n) 2. Nf3 *";
+    let game = PgnGame::parse(pgn).unwrap();
+    assert_eq!(game.mainline.comment.as_deref(), Some("Opening comment"));
+    assert_eq!(game.result, "*");
+
+    let moves = &game.mainline.moves;
+    assert_eq!(moves.len(), 3);
+    assert_eq!(moves[0].nags, vec![1]);
+    assert_eq!(moves[1].nags, vec![5]);
+
+    let sicilian = &moves[1].variations[0];
+    assert_eq!(sicilian.moves.iter().map(|m| m.san.as_str()).collect::<Vec<_>>(), ["c5", "Nf3", "d6"]);
+    assert_eq!(sicilian.moves[0].comment.as_deref(), Some("Sicilian"));
+    assert_eq!(sicilian.moves[1].variations[0].moves[0].san, "c3");
+    assert_eq!(sicilian.moves[2].comment.as_deref(), Some("rest of line"));
+}
+
+#[test]
+fn reads_setup_positions() {
+    let pgn = "[SetUp \"1\"]\n[FEN \"4k3/8/8/8/8/8/4P3/4K3 b - - 0 40\"]\n\n40... Kd7 41. e4 1/2-1/2";
+    let game = PgnGame::parse(pgn).unwrap();
+    assert_eq!(game.mainline.moves.len(), 2);
+    assert_eq!(game.result, "1/2-1/2");
+    assert!(game.to_pgn().ends_with("[FEN \"4k3/8/8/8/8/8/4P3/4K3 b - - 0 40\"]\n\n40... Kd7 41. e4 1/2-1/2\n"));
+}
+
+#[test]
+fn rejects_broken_games() {
+    assert_eq!(PgnGame::parse("1. e4 e4").err(), Some(PgnError::Move(SanError::IllegalMove(String::from("e4")))));
+    assert_eq!(PgnGame::parse("1. e4 (1. d4").err(), Some(PgnError::UnbalancedVariation));
+    assert_eq!(PgnGame::parse("1. e4 ) e5").err(), Some(PgnError::UnbalancedVariation));
+    assert_eq!(PgnGame::parse("(1. d4) 1. e4").err(), Some(PgnError::VariationWithoutMove));
+    assert_eq!(PgnGame::parse("1. e4 {never closed").err(), Some(PgnError::UnterminatedComment));
+    assert_eq!(PgnGame::parse("[Event Paris]\n1. e4").err(), Some(PgnError::InvalidTag(String::from("[Event Paris]"))));
+    assert!(matches!(PgnGame::parse("[FEN \"8/8/8/8 w - - 0 1\"]\n*"), Err(PgnError::Fen(_))));
+}
+
+#[test]
+fn writes_wrapped_movetext() {
+    let game = PgnGame::parse(OPERA_GAME).unwrap();
+    let pgn = game.to_pgn();
+    assert!(pgn.starts_with("[Event \"Paris\"]\n[Site \"Paris FRA\"]\n[Date \"1858.??.??\"]\n[Round \"?\"]\n"));
+    assert!(pgn.contains("[Result \"1-0\"]\n[ECO \"C41\"]\n\n1. e4 e5"));
+    assert!(pgn.contains("Bg4 {This is a weak move already.} 4. dxe5"));
+    assert!(pgn.lines().all(|line| line.len() <= 79));
+    assert!(pgn.ends_with("Rd8# 1-0\n"));
+
+    assert_eq!(PgnGame::parse(&pgn).unwrap(), game);
+}
+
+#[test]
+fn writes_variations_round_trip() {
+    let pgn = "1. e4 e5 (1... c5 $2 {Sicilian} 2. Nf3 (2. c3 d5) 2... d6) 2. Nf3 {Main} 2... Nc6 *";
+    let game = PgnGame::parse(pgn).unwrap();
+    let written = game.to_pgn();
+    assert!(written.ends_with("\n\n1. e4 e5 (1... c5 $2 {Sicilian} 2. Nf3 (2. c3 d5) 2... d6) 2. Nf3 {Main} 2...\nNc6 *\n"));
+    assert_eq!(PgnGame::parse(&written).unwrap().mainline, game.mainline);
+}
+
+#[test]
+fn writes_games_from_moves() {
+    let mut engine = Engine::new();
+    engine.parse_fen(STARTPOS_FEN).unwrap();
+    let moves: Vec<_> = ["f3", "e5", "g4", "Qh4#"].iter().map(|san| {
+        let mov = engine.move_from_san(san).unwrap();
+        engine.play_move(mov);
+        mov
+    }).collect();
+
+    let mut game = PgnGame::from_moves(STARTPOS_FEN, &moves).unwrap();
+    game.set_tag("White", "Fool");
+    game.result = String::from("0-1");
+    assert_eq!(game.to_pgn(), "[Event \"?\"]\n[Site \"?\"]\n[Date \"????.??.??\"]\n[Round \"?\"]\n[White \"Fool\"]\n[Black \"?\"]\n[Result \"0-1\"]\n\n1. f3 e5 2. g4 Qh4# 0-1\n");
+
+    assert!(matches!(PgnGame::from_moves(STARTPOS_FEN, &moves[1..]), Err(PgnError::Move(_))));
+}
+
+#[test]
+fn streams_multiple_games() {
+    let text = format!("{}\n[Event \"Second\"]\n\n1. d4 {{[not a tag]\n[still a comment]}} d5 *\n\n1. c4 *\n", OPERA_GAME);
+    let games: Vec<_> = PgnReader::new(text.as_bytes()).collect();
+    assert_eq!(games.len(), 3);
+    assert_eq!(games[0].as_ref().unwrap().mainline.moves.len(), 33);
+
+    let second = games[1].as_ref().unwrap();
+    assert_eq!(second.tag("Event"), Some("Second"));
+    assert_eq!(second.mainline.moves[0].comment.as_deref(), Some("[not a tag] [still a comment]"));
+    assert_eq!(second.mainline.moves.len(), 2);
+
+    assert_eq!(games[2].as_ref().unwrap().mainline.moves[0].san, "c4");
+}
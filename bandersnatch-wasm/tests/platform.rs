@@ -0,0 +1,41 @@
+mod common;
+
+use std::sync::{Arc, Mutex, atomic::{AtomicU32, Ordering}};
+
+use bandersnatch_wasm::{fen::STARTPOS_FEN, platform::Clock};
+
+use common::{CollectingSink, from_fen};
+
+// moves on by a millisecond every time it is read, so how long a search takes only depends on what it does
+struct TickingClock(Arc<AtomicU32>);
+impl Clock for TickingClock {
+    fn now(&self) -> u32 {
+        self.0.fetch_add(1, Ordering::Relaxed)
+    }
+}
+
+#[test]
+fn searches_stop_at_the_time_budget() {
+    let time = Arc::new(AtomicU32::new(0));
+    let infos = Arc::new(Mutex::new(vec![]));
+    let mut engine = from_fen(STARTPOS_FEN);
+    engine.set_clock(Box::new(TickingClock(time.clone())));
+    engine.set_info_sink(Box::new(CollectingSink(infos.clone())));
+    engine.update_max_search_time(2000);
+    engine.update_max_search_depth(30);
+
+    let start = time.load(Ordering::Relaxed);
+    engine.find_best_move_iterative();
+    let elapsed = time.load(Ordering::Relaxed) - start;
+
+    // the budget ran out long before the depth limit, but not before the first iteration,
+    // and the search either aborted at it or did not start an iteration it could not finish
+    let depth = engine.depth_searched_last_turn();
+    assert!(depth > 0 && depth < 30, "stopped at depth {}", depth);
+    assert!(elapsed < 2100, "took {} ms", elapsed);
+
+    // only completed iterations are reported
+    let infos = infos.lock().unwrap();
+    assert_eq!(infos.last().unwrap().depth, depth);
+    assert!(infos.iter().all(|i| i.time < 2000));
+}
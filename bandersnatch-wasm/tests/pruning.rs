@@ -0,0 +1,57 @@
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use bandersnatch_wasm::{Engine, defs::is_mate_score};
+
+use common::CollectingSink;
+
+#[test]
+fn null_move_passes_the_turn() {
+    let fen = "rnbqkbnr/ppp1pppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 3";
+    let mut engine = Engine::new();
+    engine.parse_fen(fen).unwrap();
+    let hash = engine.board_hash();
+
+    let undo = engine.make_null_move();
+    assert!(!engine.white_turn());
+    let passed = engine.to_fen();
+    assert!(passed.starts_with("rnbqkbnr/ppp1pppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR b KQkq - 0"));
+    let passed_hash = engine.board_hash();
+
+    engine.unmake_null_move(undo);
+    assert_eq!(engine.to_fen(), fen);
+    assert_eq!(engine.board_hash(), hash);
+
+    // the same hash as reaching the position normally, so table entries are shared
+    engine.parse_fen(&passed).unwrap();
+    assert_eq!(engine.board_hash(), passed_hash);
+}
+
+#[test]
+fn pruning_keeps_the_tree_small() {
+    let mut engine = Engine::new();
+    engine.parse_fen("2rq1rk1/pp1bppbp/3p1np1/8/3NP3/1BN1BP2/PPPQ2PP/2KR3R b - - 0 11").unwrap();
+    engine.update_max_search_time(u32::MAX);
+    engine.update_max_search_depth(5);
+    engine.find_best_move_iterative();
+    // plain alpha-beta needs over 400k nodes here
+    assert!(engine.nodes_searched() < 200_000, "{} nodes", engine.nodes_searched());
+}
+
+#[test]
+fn windows_widen_to_a_score_far_from_the_last() {
+    let infos = Arc::new(Mutex::new(vec![]));
+    let mut engine = Engine::new();
+    engine.set_info_sink(Box::new(CollectingSink(infos.clone())));
+    engine.parse_fen("2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 w - - 0 1").unwrap();
+    engine.update_max_search_time(u32::MAX);
+    engine.find_best_move_iterative();
+
+    // the mate only shows up at depth 4, far above the window of depth 3's score
+    let infos = infos.lock().unwrap();
+    let (last, earlier) = infos.split_last().unwrap();
+    assert!(earlier.iter().all(|i| !is_mate_score(i.score)));
+    assert!(is_mate_score(last.score));
+    assert_eq!(last.pv[0].to_uci(), "g3g6");
+}
@@ -0,0 +1,79 @@
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use bandersnatch_wasm::{Engine, defs::Move, fen::STARTPOS_FEN, pv::PvTable};
+
+use common::CollectingSink;
+
+#[test]
+fn table_builds_lines_from_the_bottom_up() {
+    let (a, b, c) = (Move::new(52, 36, 1), Move::new(12, 28, 1), Move::new(62, 45, 0));
+    let mut table = PvTable::new();
+    table.clear(0);
+    table.clear(1);
+    table.clear(2);
+    table.update(2, c);
+    table.update(1, b);
+    table.update(0, a);
+    assert_eq!(table.line(), [a, b, c]);
+
+    // a new best move at the root whose reply has no line yet
+    table.clear(1);
+    table.update(0, c);
+    assert_eq!(table.line(), [c]);
+}
+
+#[test]
+fn numbers_lines_from_the_current_move() {
+    let mut engine = Engine::new();
+    engine.parse_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1").unwrap();
+    let moves = [engine.move_from_san("e5").unwrap()];
+    engine.play_move(moves[0]);
+    let reply = engine.move_from_san("Nf3").unwrap();
+    engine.undo_move();
+
+    assert_eq!(engine.line_to_san(&[moves[0], reply]), "1... e5 2. Nf3");
+    assert_eq!(engine.line_to_san(&[]), "");
+    assert_eq!(engine.to_fen(), "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1");
+}
+
+#[test]
+fn every_iteration_reports_a_playable_line() {
+    let infos = Arc::new(Mutex::new(vec![]));
+    let mut engine = Engine::new();
+    engine.set_info_sink(Box::new(CollectingSink(infos.clone())));
+    engine.parse_fen(STARTPOS_FEN).unwrap();
+    engine.update_max_search_time(u32::MAX);
+    engine.update_max_search_depth(4);
+    engine.find_best_move_iterative();
+
+    let infos = infos.lock().unwrap();
+    assert_eq!(infos.len(), 4);
+    for info in infos.iter() {
+        assert_eq!(info.pv[0], info.best_move);
+        assert!(info.pv.len() as i32 <= info.depth);
+        assert!(info.pv_san.starts_with("1. "));
+    }
+    assert!(infos[3].pv.len() >= 2);
+
+    let pv = engine.principal_variation().to_vec();
+    assert_eq!((pv[0].from() as i32, pv[0].to() as i32), (engine.best_move().from, engine.best_move().to));
+    assert_eq!(engine.principal_variation_uci().split(' ').count(), pv.len());
+    for mov in pv {
+        assert!(engine.legal_moves().contains(&mov));
+        engine.play_move(mov);
+    }
+}
+
+#[test]
+fn mating_line_ends_in_mate() {
+    let mut engine = Engine::new();
+    engine.parse_fen("2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 w - - 0 1").unwrap();
+    engine.update_max_search_time(u32::MAX);
+    engine.find_best_move_iterative();
+
+    assert_eq!(engine.principal_variation().len(), 3);
+    let san = engine.principal_variation_san();
+    assert!(san.starts_with("1. Qg6") && san.ends_with('#'), "{}", san);
+}
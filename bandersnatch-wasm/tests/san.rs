@@ -0,0 +1,104 @@
+mod common;
+
+use bandersnatch_wasm::{Engine, fen::STARTPOS_FEN, san::SanError};
+
+use common::from_fen;
+
+const KIWIPETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+
+fn san(engine: &mut Engine, from: usize, to: usize) -> String {
+    let mov = engine.legal_moves().into_iter().find(|m| m.from() == from && m.to() == to).unwrap();
+    engine.move_to_san(mov)
+}
+
+#[test]
+fn piece_and_pawn_moves() {
+    let mut engine = from_fen(STARTPOS_FEN);
+    assert_eq!(san(&mut engine, 52, 36), "e4");
+    assert_eq!(san(&mut engine, 62, 45), "Nf3");
+
+    let mut engine = from_fen(KIWIPETE);
+    assert_eq!(san(&mut engine, 28, 13), "Nxf7");
+    assert_eq!(san(&mut engine, 27, 20), "dxe6");
+    assert_eq!(san(&mut engine, 60, 62), "O-O");
+    assert_eq!(san(&mut engine, 60, 58), "O-O-O");
+}
+
+#[test]
+fn disambiguation() {
+    // knights on b1 and f3 both reach d2, rooks on a1 and a5 both reach a3
+    let mut engine = from_fen("4k3/8/8/R7/8/8/8/RN2KN2 w - - 0 1");
+    assert_eq!(san(&mut engine, 57, 51), "Nbd2");
+    assert_eq!(san(&mut engine, 24, 40), "R5a3");
+    assert_eq!(san(&mut engine, 56, 40), "R1a3");
+
+    // queens on a1, a3 and c1 all reach b2, so the a1 queen needs file and rank
+    let mut engine = from_fen("4k3/8/8/8/8/Q7/8/Q1Q1K3 w - - 0 1");
+    assert_eq!(san(&mut engine, 56, 49), "Qa1b2");
+    assert_eq!(san(&mut engine, 40, 49), "Q3b2");
+    assert_eq!(san(&mut engine, 58, 49), "Qcb2");
+}
+
+#[test]
+fn promotions_checks_and_mate() {
+    let mut engine = from_fen("1r2k3/P7/8/8/8/8/8/4K3 w - - 0 1");
+    assert_eq!(san(&mut engine, 8, 0), "a8=Q");
+    assert_eq!(san(&mut engine, 8, 1), "axb8=Q+");
+
+    let mov = engine.move_from_san("axb8=N").unwrap();
+    assert_eq!(engine.move_to_san(mov), "axb8=N");
+
+    let mut engine = from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1");
+    assert_eq!(san(&mut engine, 56, 0), "Ra8#");
+    assert_eq!(san(&mut engine, 56, 8), "Ra7");
+}
+
+#[test]
+fn parses_every_legal_move_back() {
+    for fen in [STARTPOS_FEN, KIWIPETE, "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1"] {
+        let mut engine = from_fen(fen);
+        for mov in engine.legal_moves() {
+            let san = engine.move_to_san(mov);
+            assert_eq!(engine.move_from_san(&san), Ok(mov), "{} in {}", san, fen);
+        }
+    }
+}
+
+#[test]
+fn lenient_parsing() {
+    let mut engine = from_fen(KIWIPETE);
+    let castle = engine.move_from_san("O-O").unwrap();
+    assert_eq!(engine.move_from_san("0-0"), Ok(castle));
+    assert_eq!(engine.move_from_san("Nxf7!?"), engine.move_from_san("Nf7"));
+    assert_eq!(engine.move_from_san("Ne5xf7"), engine.move_from_san("Nxf7"));
+
+    let mut engine = from_fen("4k3/P7/8/8/8/8/8/4K3 w - - 0 1");
+    assert_eq!(engine.move_from_san("a8Q+"), engine.move_from_san("a8=Q"));
+}
+
+#[test]
+fn rejects_bad_san() {
+    let mut engine = from_fen(STARTPOS_FEN);
+    assert_eq!(engine.move_from_san("e5"), Err(SanError::IllegalMove(String::from("e5"))));
+    assert_eq!(engine.move_from_san("O-O"), Err(SanError::IllegalMove(String::from("O-O"))));
+    assert_eq!(engine.move_from_san("Zf3"), Err(SanError::InvalidSyntax(String::from("Zf3"))));
+    assert_eq!(engine.move_from_san("e9"), Err(SanError::InvalidSyntax(String::from("e9"))));
+    assert_eq!(engine.move_from_san(""), Err(SanError::InvalidSyntax(String::new())));
+
+    let mut engine = from_fen("4k3/8/8/8/8/8/8/RN2KN2 w - - 0 1");
+    assert_eq!(engine.move_from_san("Nd2"), Err(SanError::AmbiguousMove(String::from("Nd2"))));
+
+    // promoting without naming the piece is not a move
+    let mut engine = from_fen("4k3/P7/8/8/8/8/8/4K3 w - - 0 1");
+    assert_eq!(engine.move_from_san("a8"), Err(SanError::IllegalMove(String::from("a8"))));
+}
+
+#[test]
+fn game_moves_record_san() {
+    let mut engine = from_fen(STARTPOS_FEN);
+    for san in ["e4", "e5", "Nf3", "Nc6", "Bb5", "a6", "Ba4", "Nf6", "O-O"] {
+        let mov = engine.move_from_san(san).unwrap();
+        engine.play_move(mov);
+        assert_eq!(engine.last_move_san(), san);
+    }
+}
@@ -0,0 +1,93 @@
+use bandersnatch_wasm::{Engine, fen::STARTPOS_FEN, status::{GameStatus, Side}};
+
+fn status(fen: &str) -> GameStatus {
+    let mut engine = Engine::new();
+    engine.parse_fen(fen).unwrap();
+    engine.game_status()
+}
+
+fn play(engine: &mut Engine, moves: &[&str]) {
+    for san in moves {
+        let mov = engine.move_from_san(san).unwrap();
+        engine.play_move(mov);
+    }
+}
+
+#[test]
+fn detects_mate_and_stalemate() {
+    let mut engine = Engine::new();
+    engine.parse_fen(STARTPOS_FEN).unwrap();
+    assert_eq!(engine.game_status(), GameStatus::Ongoing);
+    play(&mut engine, &["f3", "e5", "g4", "Qh4#"]);
+    assert_eq!(engine.game_status(), GameStatus::Checkmate(Side::Black));
+    assert_eq!(engine.game_result(), "0-1");
+    assert!(engine.is_checkmate());
+    assert!(!engine.check_for_draw());
+
+    assert_eq!(status("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1"), GameStatus::Stalemate);
+    // mate on the move that reaches the move limit still counts
+    assert_eq!(status("R5k1/5ppp/8/8/8/8/8/6K1 b - - 150 90"), GameStatus::Checkmate(Side::White));
+}
+
+#[test]
+fn detects_insufficient_material() {
+    assert_eq!(status("8/8/4k3/8/8/4K3/8/8 w - - 0 1"), GameStatus::InsufficientMaterial);
+    assert_eq!(status("8/8/4k3/8/8/3NK3/8/8 w - - 0 1"), GameStatus::InsufficientMaterial);
+    assert_eq!(status("8/8/4k3/8/8/3BK3/8/8 b - - 0 1"), GameStatus::InsufficientMaterial);
+    assert_eq!(status("8/8/4k1b1/8/8/3BK3/8/8 w - - 0 1"), GameStatus::InsufficientMaterial);
+    assert_eq!(status("8/8/4kb2/8/8/3BK3/8/8 w - - 0 1"), GameStatus::Ongoing);
+    assert_eq!(status("8/8/4k3/8/8/2NNK3/8/8 w - - 0 1"), GameStatus::Ongoing);
+    assert_eq!(status("8/8/4k3/8/8/3PK3/8/8 w - - 0 1"), GameStatus::Ongoing);
+}
+
+#[test]
+fn move_counters_claim_then_force_a_draw() {
+    let fifty = status("8/8/4k3/8/8/4K3/8/R7 w - - 100 80");
+    assert_eq!(fifty, GameStatus::FiftyMove);
+    assert!(fifty.is_claimable_draw() && !fifty.is_over());
+    assert_eq!(status("8/8/4k3/8/8/4K3/8/R7 w - - 99 80"), GameStatus::Ongoing);
+
+    let seventy_five = status("8/8/4k3/8/8/4K3/8/R7 w - - 150 100");
+    assert_eq!(seventy_five, GameStatus::SeventyFiveMove);
+    assert!(!seventy_five.is_claimable_draw() && seventy_five.is_over());
+    assert_eq!(seventy_five.result(), "1/2-1/2");
+}
+
+#[test]
+fn repetitions_claim_then_force_a_draw() {
+    let mut engine = Engine::new();
+    engine.parse_fen(STARTPOS_FEN).unwrap();
+    let shuffle = ["Nf3", "Nf6", "Ng1", "Ng8"];
+
+    play(&mut engine, &shuffle);
+    play(&mut engine, &shuffle[..3]);
+    assert_eq!(engine.game_status(), GameStatus::Ongoing);
+
+    // black can repeat too, not only white
+    play(&mut engine, &shuffle[3..]);
+    play(&mut engine, &shuffle[..1]);
+    assert_eq!(engine.repetition_count(), 3);
+    assert_eq!(engine.game_status(), GameStatus::Threefold);
+    assert!(engine.is_claimable_draw() && engine.check_for_draw());
+
+    play(&mut engine, &shuffle[1..]);
+    play(&mut engine, &shuffle);
+    play(&mut engine, &shuffle[..1]);
+    assert_eq!(engine.game_status(), GameStatus::Fivefold);
+    assert_eq!(engine.game_status_text(), "Fivefold repetition");
+}
+
+#[test]
+fn repetitions_count_the_position_after_a_pawn_move() {
+    let mut engine = Engine::new();
+    engine.parse_fen(STARTPOS_FEN).unwrap();
+    play(&mut engine, &["e4", "e5", "Nf3", "Nf6", "Ng1", "Ng8", "Nf3", "Nf6", "Ng1", "Ng8"]);
+    assert_eq!(engine.repetition_count(), 3);
+    assert_eq!(engine.game_status(), GameStatus::Threefold);
+
+    // the history is rebuilt the same way when stepping through the game
+    assert!(engine.undo_move());
+    assert_eq!(engine.game_status(), GameStatus::Ongoing);
+    assert!(engine.redo_move());
+    assert_eq!(engine.game_status(), GameStatus::Threefold);
+}
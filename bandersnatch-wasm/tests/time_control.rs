@@ -0,0 +1,152 @@
+use std::sync::{Arc, atomic::{AtomicU32, Ordering}};
+
+use bandersnatch_wasm::{Engine, defs::Move, fen::STARTPOS_FEN, platform::Clock, time_control::{ChessClock, SearchBudget, TimeControl, TimeManager, allocate}};
+
+fn play(engine: &mut Engine, san: &str) {
+    let mov = engine.move_from_san(san).unwrap();
+    engine.play_move(mov);
+}
+
+// a clock that only moves when the test advances it
+struct ManualClock(Arc<AtomicU32>);
+impl Clock for ManualClock {
+    fn now(&self) -> u32 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+#[test]
+fn increments_and_delays_are_applied() {
+    let mut fischer = ChessClock::new(TimeControl::Fischer { base: 60000, increment: 2000 });
+    fischer.record_move(true, 5000);
+    assert_eq!(fischer.remaining(true), 57000);
+    assert_eq!(fischer.remaining(false), 60000);
+
+    let mut bronstein = ChessClock::new(TimeControl::Bronstein { base: 60000, delay: 3000 });
+    bronstein.record_move(true, 2000);
+    bronstein.record_move(false, 5000);
+    assert_eq!(bronstein.remaining(true), 60000);
+    assert_eq!(bronstein.remaining(false), 58000);
+
+    let mut delay = ChessClock::new(TimeControl::SimpleDelay { base: 60000, delay: 3000 });
+    delay.record_move(true, 2000);
+    delay.record_move(false, 5000);
+    assert_eq!(delay.remaining(true), 60000);
+    assert_eq!(delay.remaining(false), 58000);
+
+    let mut sudden_death = ChessClock::new(TimeControl::SuddenDeath { base: 1000 });
+    sudden_death.record_move(true, 1000);
+    assert!(sudden_death.is_flagged(true));
+    assert!(!sudden_death.is_flagged(false));
+}
+
+#[test]
+fn periods_add_time_after_the_last_move() {
+    let mut clock = ChessClock::new(TimeControl::MovesToGo { base: 10000, moves: 2 });
+    assert_eq!(clock.moves_to_go(true), Some(2));
+    clock.record_move(true, 3000);
+    assert_eq!(clock.moves_to_go(true), Some(1));
+    assert_eq!(clock.moves_to_go(false), Some(2));
+
+    clock.record_move(true, 3000);
+    assert_eq!(clock.remaining(true), 14000);
+    assert_eq!(clock.moves_to_go(true), Some(2));
+}
+
+#[test]
+fn budgets_stay_inside_the_clock() {
+    let budget = allocate(60000, 0, None);
+    assert_eq!(budget.soft, 2000);
+    assert!(budget.hard > budget.soft && budget.hard <= 30000);
+
+    // the last move before the time control may use almost everything
+    let last = allocate(10000, 0, Some(1));
+    assert_eq!(last, SearchBudget { soft: 9950, hard: 9950 });
+
+    let low = allocate(30, 5000, None);
+    assert!(low.soft >= 1 && low.hard < 30);
+
+    let fischer = ChessClock::new(TimeControl::Fischer { base: 60000, increment: 1000 });
+    assert_eq!(fischer.allocate(true).soft, 2750);
+}
+
+#[test]
+fn engine_charges_the_side_that_moved() {
+    let time = Arc::new(AtomicU32::new(0));
+    let mut engine = Engine::new();
+    engine.set_clock(Box::new(ManualClock(time.clone())));
+    engine.parse_fen(STARTPOS_FEN).unwrap();
+    assert_eq!(engine.clock_remaining(true), None);
+
+    engine.set_fischer(60000, 1000);
+    time.store(4000, Ordering::Relaxed);
+    play(&mut engine, "e4");
+    time.store(5500, Ordering::Relaxed);
+    play(&mut engine, "e5");
+    assert_eq!(engine.clock_remaining(true), Some(57000));
+    assert_eq!(engine.clock_remaining(false), Some(59500));
+
+    engine.set_clock_remaining(false, 100);
+    time.store(6000, Ordering::Relaxed);
+    play(&mut engine, "Nf3");
+    assert_eq!(engine.chess_clock().unwrap().allocate(false), SearchBudget::fixed(50));
+
+    // a new game restarts both clocks
+    engine.parse_fen(STARTPOS_FEN).unwrap();
+    assert_eq!(engine.clock_remaining(false), Some(60000));
+    engine.clear_time_control();
+    assert!(engine.chess_clock().is_none());
+}
+
+#[test]
+fn stable_searches_stop_early() {
+    let (e4, d4) = (Move::new(52, 36, 1), Move::new(51, 35, 1));
+    let mut manager = TimeManager::new(SearchBudget { soft: 1000, hard: 4000 });
+    assert!(manager.should_continue(e4, 30, 1, None, 10, 5));
+    assert!(manager.should_continue(e4, 30, 1, None, 30, 15));
+    assert_eq!(manager.target(), 1000);
+
+    assert!(!manager.should_continue(e4, 25, 1, None, 600, 400));
+    assert_eq!(manager.target(), 500);
+
+    // a new best move resets the count
+    assert!(manager.should_continue(d4, 25, 2, None, 600, 10));
+    assert_eq!(manager.target(), 1500);
+}
+
+#[test]
+fn clearly_best_moves_stop_early() {
+    let e4 = Move::new(52, 36, 1);
+    let mut manager = TimeManager::new(SearchBudget { soft: 1000, hard: 4000 });
+    assert!(manager.should_continue(e4, 300, 1, Some(40), 10, 5));
+    assert_eq!(manager.target(), 1000);
+
+    // a big lead over the second line counts before the move has been stable for long
+    assert!(!manager.should_continue(e4, 300, 1, Some(250), 600, 100));
+    assert_eq!(manager.target(), 500);
+
+    manager.should_continue(e4, 300, 1, Some(250), 600, 100);
+    assert_eq!(manager.target(), 250);
+}
+
+#[test]
+fn falling_scores_get_more_time() {
+    let e4 = Move::new(52, 36, 1);
+    let mut manager = TimeManager::new(SearchBudget { soft: 1000, hard: 3000 });
+    assert!(manager.should_continue(e4, 100, 1, None, 100, 100));
+    assert!(manager.should_continue(e4, 20, 1, None, 1100, 300));
+    assert_eq!(manager.target(), 2000);
+
+    // the next depth would be aborted by the hard limit
+    assert!(!manager.should_continue(e4, -40, 1, None, 1200, 1000));
+}
+
+#[test]
+fn forced_moves_are_played_after_one_iteration() {
+    let mut engine = Engine::new();
+    engine.parse_fen("7k/8/8/8/8/8/1q6/K7 w - - 0 1").unwrap();
+    engine.update_max_search_time(u32::MAX);
+    engine.find_best_move_iterative();
+    assert_eq!(engine.depth_searched_last_turn(), 1);
+    assert_eq!(engine.best_move().to, 49);
+}
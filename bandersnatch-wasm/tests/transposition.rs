@@ -0,0 +1,110 @@
+use bandersnatch_wasm::{Engine, defs::{LARGEST_SAFE_I32, Move, SMALLEST_SAFE_I32, SavedEvalType, mate_in}, fen::STARTPOS_FEN, transposition::TranspositionTable};
+
+// hashes that share a bucket but not a key
+fn hash(key: u64) -> u64 {
+    key << 32 | 7
+}
+
+#[test]
+fn stores_and_verifies_keys() {
+    let mut table = TranspositionTable::new(1);
+    assert_eq!(table.size(), 1);
+    assert!(table.probe(hash(1), 0).is_none());
+    assert_eq!(table.hash_full(), 0);
+
+    let mov = Move::new(52, 36, 1);
+    table.store(hash(1), mov, -250, 6, SavedEvalType::Beta, 0);
+
+    let entry = table.probe(hash(1), 0).unwrap();
+    assert_eq!((entry.mov(), entry.score(), entry.depth(), entry.bound()), (mov, -250, 6, SavedEvalType::Beta));
+    assert!(table.probe(hash(2), 0).is_none());
+
+    // a bound without a move keeps the move already known for the position
+    table.store(hash(1), Move::NULL, 40, 7, SavedEvalType::Alpha, 0);
+    assert_eq!(table.probe(hash(1), 0).unwrap().mov(), mov);
+    assert_eq!(table.probe(hash(1), 0).unwrap().bound(), SavedEvalType::Alpha);
+
+    table.clear();
+    assert!(table.probe(hash(1), 0).is_none());
+}
+
+#[test]
+fn replaces_shallow_and_old_entries_first() {
+    let mut table = TranspositionTable::new(1);
+    for key in 1..=4 {
+        table.store(hash(key), Move::NULL, 0, key as i32 + 4, SavedEvalType::Exact, 0);
+    }
+    table.store(hash(5), Move::NULL, 0, 1, SavedEvalType::Exact, 0);
+    assert!(table.probe(hash(1), 0).is_none());
+    assert!(table.probe(hash(5), 0).is_some());
+
+    // after a few searches depth no longer protects an entry
+    for _ in 0..3 {
+        table.new_search();
+    }
+    table.store(hash(5), Move::NULL, 0, 1, SavedEvalType::Exact, 0);
+    table.store(hash(6), Move::NULL, 0, 2, SavedEvalType::Exact, 0);
+    assert!(table.probe(hash(2), 0).is_none());
+    assert!(table.probe(hash(5), 0).is_some() && table.probe(hash(6), 0).is_some());
+}
+
+#[test]
+fn deep_results_are_not_replaced_by_shallow_bounds() {
+    let mut table = TranspositionTable::new(1);
+    table.store(hash(1), Move::NULL, 30, 8, SavedEvalType::Exact, 0);
+    table.store(hash(1), Move::NULL, 90, 2, SavedEvalType::Beta, 0);
+    assert_eq!(table.probe(hash(1), 0).unwrap().depth(), 8);
+
+    table.new_search();
+    table.store(hash(1), Move::NULL, 90, 2, SavedEvalType::Beta, 0);
+    assert_eq!(table.probe(hash(1), 0).unwrap().depth(), 2);
+}
+
+#[test]
+fn table_is_kept_between_moves() {
+    let mut engine = Engine::new();
+    engine.parse_fen(STARTPOS_FEN).unwrap();
+    engine.set_hash_size(1);
+    assert_eq!(engine.hash_full(), 0);
+
+    engine.update_max_search_depth(4);
+    engine.update_max_search_time(u32::MAX);
+    engine.find_best_move_iterative();
+    let filled = engine.hash_full();
+    assert!(filled > 0);
+
+    let mov = engine.move_from_san("e4").unwrap();
+    engine.play_move(mov);
+    assert_eq!(engine.hash_full(), filled);
+}
+
+#[test]
+fn mate_scores_are_stored_relative_to_the_node() {
+    let mut table = TranspositionTable::new(1);
+    // mating at ply 7 seen from ply 4 is a mate 3 plies away from that node
+    table.store(hash(1), Move::NULL, LARGEST_SAFE_I32 - 7, 5, SavedEvalType::Exact, 4);
+    assert_eq!(table.probe(hash(1), 4).unwrap().score(), LARGEST_SAFE_I32 - 7);
+    assert_eq!(table.probe(hash(1), 2).unwrap().score(), LARGEST_SAFE_I32 - 5);
+
+    table.store(hash(2), Move::NULL, SMALLEST_SAFE_I32 + 6, 5, SavedEvalType::Exact, 2);
+    assert_eq!(table.probe(hash(2), 0).unwrap().score(), SMALLEST_SAFE_I32 + 4);
+
+    table.store(hash(3), Move::NULL, 120, 5, SavedEvalType::Exact, 2);
+    assert_eq!(table.probe(hash(3), 9).unwrap().score(), 120);
+}
+
+#[test]
+fn reports_mate_in_moves() {
+    assert_eq!(mate_in(LARGEST_SAFE_I32 - 1), Some(1));
+    assert_eq!(mate_in(LARGEST_SAFE_I32 - 3), Some(2));
+    assert_eq!(mate_in(SMALLEST_SAFE_I32 + 2), Some(-1));
+    assert_eq!(mate_in(350), None);
+
+    let mut engine = Engine::new();
+    engine.parse_fen("2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 w - - 0 1").unwrap();
+    engine.update_max_search_time(u32::MAX);
+    engine.find_best_move_iterative();
+    assert_eq!(engine.best_move_mate_in(), Some(2));
+    // the search stops once the mate is proven
+    assert!(engine.depth_searched_last_turn() <= 4);
+}
@@ -0,0 +1,62 @@
+mod common;
+
+use bandersnatch_wasm::{Engine, defs::{MoveError, MoveFlag, Piece}, fen::STARTPOS_FEN, uci::UciError};
+
+use common::from_fen;
+
+const PROMOTION: &str = "1r2k3/P7/8/8/8/8/8/4K3 w - - 0 1";
+
+#[test]
+fn every_legal_move_round_trips() {
+    let mut engine = from_fen("r2q1rk1/pP1p2pp/Q4n2/bbp1p3/Np6/1B3NBn/pPPP1PPP/R3K2R b KQ - 0 1");
+    for mov in engine.legal_moves() {
+        assert_eq!(engine.parse_uci_move(&mov.to_uci()), Ok(mov));
+    }
+}
+
+#[test]
+fn parses_promotions() {
+    let mut engine = from_fen(PROMOTION);
+    let knight = engine.parse_uci_move("a7b8n").unwrap();
+    assert_eq!(knight.flags(), MoveFlag::KNIGHT_PROMOTION | MoveFlag::CAPTURE);
+    assert_eq!(knight.to_uci(), "a7b8n");
+    assert_eq!(engine.parse_uci_move("A7A8Q").unwrap().promotion_piece(), Piece::Queen_W);
+    assert_eq!(engine.parse_uci_move("a7a8"), Err(UciError::MissingPromotion(String::from("a7a8"))));
+}
+
+#[test]
+fn rejects_bad_moves() {
+    let mut engine = from_fen(STARTPOS_FEN);
+    assert_eq!(engine.parse_uci_move("e2e5"), Err(UciError::IllegalMove(String::from("e2e5"))));
+    assert_eq!(engine.parse_uci_move("e2e4q"), Err(UciError::IllegalMove(String::from("e2e4q"))));
+    assert_eq!(engine.parse_uci_move("e2e4k"), Err(UciError::InvalidSyntax(String::from("e2e4k"))));
+    assert_eq!(engine.parse_uci_move("e2"), Err(UciError::InvalidSyntax(String::from("e2"))));
+    assert_eq!(engine.parse_uci_move("i2i4"), Err(UciError::InvalidSyntax(String::from("i2i4"))));
+}
+
+#[test]
+fn attempt_move_uci_plays_the_move() {
+    let mut engine = from_fen(PROMOTION);
+    assert!(engine.attempt_move_uci("a7b8r").unwrap());
+    assert_eq!(engine.to_fen(), "1R2k3/8/8/8/8/8/8/4K3 b - - 0 1");
+    assert_eq!(engine.last_move_san(), "axb8=R+");
+
+    assert!(engine.attempt_move_uci("e8e7").is_ok());
+    assert_eq!(Engine::move_to_uci(62, 45, Piece::Empty), "g1f3");
+    assert_eq!(Engine::move_to_uci(8, 0, Piece::Knight_W), "a7a8n");
+}
+
+#[test]
+fn attempt_move_validates_promotions() {
+    let mut engine = from_fen(PROMOTION);
+    assert_eq!(engine.attempt_move(8, 0, Piece::Empty), Err(MoveError::MissingPromotion));
+    assert_eq!(engine.attempt_move(8, 0, Piece::Knight_B), Err(MoveError::InvalidPromotion(Piece::Knight_B)));
+    assert_eq!(engine.attempt_move(8, 0, Piece::King_W), Err(MoveError::InvalidPromotion(Piece::King_W)));
+    assert_eq!(engine.attempt_move(60, 59, Piece::Queen_W), Err(MoveError::InvalidPromotion(Piece::Queen_W)));
+    assert_eq!(engine.attempt_move(60, 44, Piece::Empty), Ok(false));
+    assert_eq!(engine.to_fen(), PROMOTION);
+
+    assert_eq!(engine.attempt_move(8, 0, Piece::Knight_W), Ok(true));
+    assert_eq!(engine.last_move_san(), "a8=N");
+    assert_eq!(engine.to_fen(), "Nr2k3/8/8/8/8/8/8/4K3 b - - 0 1");
+}
@@ -27,6 +27,40 @@ export interface EvalMove {
     to: number;
     data: number;
     score: number;
+    flags?: number;
+}
+
+// mirrors MoveFlag in the wasm engine
+export enum MoveFlag {
+    Quiet = 0,
+    DoublePawnPush = 1,
+    KingCastle = 2,
+    QueenCastle = 3,
+    Capture = 4,
+    EnPassant = 5,
+    KnightPromotion = 8,
+    BishopPromotion = 9,
+    RookPromotion = 10,
+    QueenPromotion = 11
+}
+
+// decodes a packed 16 bit move from the wasm engine (from | to << 6 | flags << 12)
+export const unpackMove = (packed: number): EvalMove => {
+    const to = (packed >> 6) & 0x3F;
+    const flags = packed >> 12;
+    let data = Piece.Empty;
+    if ((flags & MoveFlag.KnightPromotion) != 0) {
+        const promotions = [Piece.Knight_B, Piece.Bishop_B, Piece.Rook_B, Piece.Queen_B];
+        data = promotions[flags & 3] + (to < 8 ? 6 : 0);
+    }
+
+    return {
+        from: packed & 0x3F,
+        to: to,
+        data: data,
+        score: 0,
+        flags: flags
+    };
 }
 
 export interface HistoricalBoard {
@@ -6,10 +6,12 @@ import('bandersnatch-wasm');
 // We alias self to ctx and give it our newly created type
 const ctx: Worker = self as any;
 
-(self as any).post_eval_message = (s: string, evaluation: number) => {
+(self as any).post_eval_message = (s: string, evaluation: number, pv: string) => {
     ctx.postMessage({
         command: EvalCommands.ReceiveCurrentEval,
-        eval: evaluation
+        eval: evaluation,
+        mate: s,
+        pv: pv
     });
 }
 
@@ -1,4 +1,4 @@
-import { EvalCommands, EngineCommands, HistoricalBoard, EvalMove, DebugMoveOutput, Piece, notationToIndex, fenToPieceDict, getPieceNameShort, indexToNotation } from "../definitions";
+import { EvalCommands, EngineCommands, HistoricalBoard, EvalMove, DebugMoveOutput, Piece, unpackMove } from "../definitions";
 import { openings } from "./openings";
 
 import('bandersnatch-wasm');
@@ -6,10 +6,12 @@ import('bandersnatch-wasm');
 // We alias self to ctx and give it our newly created type
 const ctx: Worker = self as any;
 
-(self as any).post_eval_message = (s: string, evaluation: number) => {
+(self as any).post_eval_message = (s: string, evaluation: number, pv: string) => {
     ctx.postMessage({
         command: EvalCommands.ReceiveCurrentEval,
-        eval: evaluation
+        eval: evaluation,
+        mate: s,
+        pv: pv
     });
 }
 
@@ -48,17 +50,11 @@ export class WasmEngine {
 
         const moves_ptr = this.wasm_engine.valid_moves_ptr();
         const moves_len = this.wasm_engine.valid_moves_len();
-        const valid_move_data = new Int32Array(this.memory.buffer, moves_ptr, moves_len * 4);
+        const valid_move_data = new Uint16Array(this.memory.buffer, moves_ptr, moves_len);
 
         let valid_moves: EvalMove[] = [];
-        for (let i = 0; i < valid_move_data.length; i += 4) {
-            let move: EvalMove = {
-                from: valid_move_data[i],
-                to: valid_move_data[i + 1],
-                data: valid_move_data[i + 2],
-                score: valid_move_data[i + 3],
-            }
-            valid_moves.push(move);
+        for (let i = 0; i < valid_move_data.length; i++) {
+            valid_moves.push(unpackMove(valid_move_data[i]));
         }
 
         return valid_moves;
@@ -69,19 +65,20 @@ export class WasmEngine {
 
         const moves_ptr = this.wasm_engine.moves_found_this_turn_ptr();
         const moves_len = this.wasm_engine.moves_found_this_turn_len();
-        const found_moves_data = new Int32Array(this.memory.buffer, moves_ptr, moves_len * 6);
+        const found_moves_data = new Int32Array(this.memory.buffer, moves_ptr, moves_len * 7);
 
         let found_moves: DebugMoveOutput[] = [];
-        for (let i = 0; i < found_moves_data.length; i += 6) {
+        for (let i = 0; i < found_moves_data.length; i += 7) {
             let move: DebugMoveOutput = {
                 move: {
                     from: found_moves_data[i],
                     to: found_moves_data[i + 1],
                     data: found_moves_data[i + 2],
-                    score: found_moves_data[i + 3]
+                    score: found_moves_data[i + 3],
+                    flags: found_moves_data[i + 4]
                 },
-                piece: found_moves_data[i + 4],
-                capture: found_moves_data[i + 5] == 1
+                piece: found_moves_data[i + 5],
+                capture: found_moves_data[i + 6] == 1
             }
             
             found_moves.push(move);
@@ -193,15 +190,15 @@ export class WasmEngine {
     best_move = () => {
         if (!this.initialized) return {} as EvalMove;
 
-        const move_ptr = this.wasm_engine.best_move();
-        const move_data = new Int32Array(this.memory.buffer, move_ptr, 4);
-
+        const best_move = this.wasm_engine.best_move();
         let move: EvalMove = {
-            from: move_data[0],
-            to: move_data[1],
-            data: move_data[2],
-            score: move_data[3],
+            from: best_move.from,
+            to: best_move.to,
+            data: best_move.data,
+            score: best_move.score,
+            flags: best_move.flags,
         }
+        best_move.free();
         
         return move;
     }
@@ -278,6 +275,14 @@ export class WasmEngine {
         this.wasm_engine.use_historical_board();
     }
 
+    // history from before the engine took over the game (see SetHistory) only exists as snapshots
+    restore_history = (stepped: boolean, board: HistoricalBoard) => {
+        if (stepped)
+            this.move_list = [...board.moveList];
+        else
+            this.use_historical_board(board);
+    }
+
     step_back = () => {
         if (!this.initialized) return;
 
@@ -285,7 +290,7 @@ export class WasmEngine {
             this.historical_index--;
 
             const board = this.historical_boards[this.historical_boards.length - 1 + this.historical_index];
-            this.use_historical_board(board);
+            this.restore_history(this.wasm_engine.undo_move(), board);
         }
     }
 
@@ -295,7 +300,7 @@ export class WasmEngine {
         if (this.historical_index < 0) {
             this.historical_index++;
             const board = this.historical_boards[this.historical_boards.length - 1 + this.historical_index];
-            this.use_historical_board(board);
+            this.restore_history(this.wasm_engine.redo_move(), board);
         }
     }
 
@@ -305,16 +310,32 @@ export class WasmEngine {
         if (this.historical_boards.length > 1 && this.historical_index == 0) {
             this.historical_index = 0;
             const board = this.historical_boards[this.historical_boards.length - 2];
-            this.use_historical_board(board);
+            this.restore_history(this.wasm_engine.undo_move(), board);
+            this.wasm_engine.truncate_game();
             this.historical_boards.pop();
         }
     }
 
+    principal_variation_san = () => {
+        if (!this.initialized) return "";
+        return this.wasm_engine.principal_variation_san();
+    }
+
     check_for_draw = () => {
         if (!this.initialized) return false;
         return this.wasm_engine.check_for_draw();
     }
 
+    is_checkmate = () => {
+        if (!this.initialized) return false;
+        return this.wasm_engine.is_checkmate();
+    }
+
+    game_status_text = () => {
+        if (!this.initialized) return "";
+        return this.wasm_engine.game_status_text();
+    }
+
     calculate_all_possible_moves = (depth: number) => {
         if (!this.initialized) return 0;
         return this.wasm_engine.calculate_all_possible_moves(depth);
@@ -335,9 +356,37 @@ export class WasmEngine {
         return this.wasm_engine.find_best_move_iterative();
     }
 
-    attempt_move = (from_index: number, to_index: number) => {
+    // throws if a promoting move has no valid promotion piece
+    attempt_move = (from_index: number, to_index: number, promotion: Piece = Piece.Empty) => {
         if (!this.initialized) return;
-        return this.wasm_engine.attempt_move(from_index, to_index);
+        return this.wasm_engine.attempt_move(from_index, to_index, promotion);
+    }
+
+    // throws if the move is malformed or illegal
+    attempt_move_uci = (uci: string) => {
+        if (!this.initialized) return false;
+        return this.wasm_engine.attempt_move_uci(uci);
+    }
+
+    move_from_uci = (uci: string) => {
+        if (!this.initialized) return {} as EvalMove;
+
+        const packed = this.wasm_engine.move_from_uci(uci);
+        let move: EvalMove = {
+            from: packed.from,
+            to: packed.to,
+            data: packed.data,
+            score: packed.score,
+            flags: packed.flags,
+        }
+        packed.free();
+
+        return move;
+    }
+
+    move_to_uci = (move: EvalMove) => {
+        if (!this.initialized) return "";
+        return this.wasm.Engine.move_to_uci(move.from, move.to, move.data);
     }
 
     find_piece_in_file = (piece: number, file: string) => {
@@ -345,72 +394,33 @@ export class WasmEngine {
         return this.wasm_engine.find_piece_in_file(piece, file);
     }
 
-    // keep some of these functions in js because they aren't bottlenecked and would be a pain to convert over
-    generate_move_string = (fromIndex: number, toIndex: number) => {
-        if (this.castled_this_turn()) {
-            if (this.white_turn()) { // todo: O-O-O
-
-            }
-            return "O-O";
-        }
+    last_move_san = () => {
+        if (!this.initialized) return "";
+        return this.wasm_engine.last_move_san();
+    }
 
-        const board = this.board();
-        if (!board)
-            return "";
+    san_to_move = (san: string) => {
+        if (!this.initialized) return {} as EvalMove;
 
-        let pieceName = getPieceNameShort(board[toIndex]).toUpperCase(); // for opening comparison
-        if (pieceName == "" && this.piece_captured_this_turn()) { // pawn capture so get the name of the file it came from
-            pieceName = indexToNotation(fromIndex)[0];
+        const packed = this.wasm_engine.san_to_move(san);
+        let move: EvalMove = {
+            from: packed.from,
+            to: packed.to,
+            data: packed.data,
+            score: packed.score,
+            flags: packed.flags,
         }
-        const newLocation = indexToNotation(toIndex);
+        packed.free();
 
-        return `${pieceName}${this.piece_captured_this_turn() ? 'x' : ''}${newLocation}${this.in_check() ? '+' : ''}`;
+        return move;
     }
 
-    book_move = () => { // a bit messy, cleanup ?
+    book_move = () => {
         try {
-            const valid_moves = this.valid_moves();
-            const board = this.board();
-
-            if (!valid_moves || !board)
-                return false;
-
+            let opening;
             if (this.move_count() == 0) { // if its move one, play a random opening
                 const index = Math.floor(Math.random() * openings.length);
-                const opening = openings[index];
-                const move = opening.moves[0];
-                move.replace(/\W/g, '');
-                const file = move[move.length - 2];
-                const rank = parseInt(move[move.length - 1]);
-                let from = -1;
-                let to = notationToIndex(rank, file);
-
-                if (move.length == 2) { // pawn move
-                    from = this.find_piece_in_file(Piece.Pawn_W, file); // always white since move zero
-                } else { // otherwise find the piece with that move as valid
-                    const pieceName = move[0];
-                    const piece = fenToPieceDict[this.white_turn() ? pieceName.toUpperCase() : pieceName.toLowerCase()];
-
-                    for (let i = 0; i < valid_moves.length; i++) {
-                        if (board[valid_moves[i].from] == piece && valid_moves[i].to == to) {
-                            from = valid_moves[i].from;
-                            break;
-                        }
-                    }
-                }
-
-                if (from == -1 || to == -1)
-                    return false;
-
-                this.current_opening = opening.name;
-                let result = this.attempt_move(from, to);
-                if (result) {
-                    this.move_list.push(move);
-                    this.book_move_from = from;
-                    this.book_move_to = to;
-                    return true;
-                }
-                return false;
+                opening = openings[index];
             } else { // otherwise we must interpret the position and decide if this opening exists
                 let validOpenings: number[] = [];
                 for (let i = 0; i < openings.length; i++) {
@@ -425,53 +435,22 @@ export class WasmEngine {
 
                 // then pick a random opening from the valid ones and make the next move
                 const index = Math.floor(Math.random() * validOpenings.length);
-                const opening = openings[validOpenings[index]];
-                const move = opening.moves[this.move_list.length];
-                move.replace(/\W/g, '');
-                const file = move[move.length - 2];
-                const rank = parseInt(move[move.length - 1]);
-                let from = -1;
-                let to = notationToIndex(rank, file);
-
-                if (move.length == 2) { // pawn move
-                    from = this.find_piece_in_file(this.white_turn() ? Piece.Pawn_W : Piece.Pawn_B, file);
-                } else { // otherwise find the piece with that move as valid
-                    const pieceName = move[0];
-                    let piece = fenToPieceDict[this.white_turn() ? pieceName.toUpperCase() : pieceName.toLowerCase()];
-                    for (let i = 0; i < valid_moves.length; i++) {
-                        if (board[valid_moves[i].from] == piece && valid_moves[i].to == to) {
-                            from = valid_moves[i].from;
-                            break;
-                        }
-                    }
-
-                    // if not found, its likely a pawn capture move
-                    piece = this.white_turn() ? Piece.Pawn_W : Piece.Pawn_B;
-                    if (from == -1 ){
-                        for (let i = 0; i < valid_moves.length; i++) {
-                            if (board[valid_moves[i].from] == piece && valid_moves[i].to == to) {
-                                from = valid_moves[i].from;
-                                break;
-                            }
-                        }
-                    }
-                }
+                opening = openings[validOpenings[index]];
+            }
 
-                if (from == -1 || to == -1)
-                    return false;
+            // throws if the book move is not legal here
+            const move = this.san_to_move(opening.moves[this.move_list.length]);
 
-                this.current_opening = opening.name;
-                let result = this.attempt_move(from, to);
-                if (result) {
-                    this.move_list.push(move);
-                    this.book_move_from = from;
-                    this.book_move_to = to;
-                    return true;
-                }
-                return false;
+            this.current_opening = opening.name;
+            let result = this.attempt_move(move.from, move.to, move.data);
+            if (result) {
+                this.move_list.push(this.last_move_san());
+                this.book_move_from = move.from;
+                this.book_move_to = move.to;
+                return true;
             }
+            return false;
         } catch (e) { // if something goes wrong, just cancel
-            //this.useHistoricalBoard(this.historicalBoards[this.historicalBoards.length - 1]);
             return false;
         }
     }
@@ -555,10 +534,14 @@ ctx.addEventListener("message", (e) => {
             let result = false;
             if (engine.historical_index == 0)
             {
-                result = engine.attempt_move(e.data.fromIndex, e.data.toIndex);
+                try {
+                    result = engine.attempt_move(e.data.fromIndex, e.data.toIndex, e.data.promotion ?? Piece.Empty);
+                } catch (error) { // missing or invalid promotion piece
+                    result = false;
+                }
                 if (result) {
                     engine.push_history();
-                    engine.move_list.push(engine.generate_move_string(e.data.fromIndex, e.data.toIndex));
+                    engine.move_list.push(engine.last_move_san());
                 }
             }
 
@@ -572,7 +555,9 @@ ctx.addEventListener("message", (e) => {
                 inCheck: engine.in_check(),
                 captured: engine.piece_captured_this_turn(),
                 castled: engine.castled_this_turn(),
-                draw: engine.check_for_draw()
+                draw: engine.check_for_draw(),
+                checkmate: engine.is_checkmate(),
+                status: engine.game_status_text()
             });
             break;
         }
@@ -623,7 +608,7 @@ ctx.addEventListener("message", (e) => {
                     engine.push_history();
                     from = engine.best_move().from;
                     to = engine.best_move().to;
-                    engine.move_list.push(engine.generate_move_string(from, to));
+                    engine.move_list.push(engine.last_move_san());
                 }
             } else {
                 engine.set_depth_searched_last_turn(-1);
@@ -646,7 +631,9 @@ ctx.addEventListener("message", (e) => {
                 inCheck: engine.in_check(),
                 captured: engine.piece_captured_this_turn(),
                 castled: engine.castled_this_turn(),
-                draw: engine.check_for_draw()
+                draw: engine.check_for_draw(),
+                checkmate: engine.is_checkmate(),
+                status: engine.game_status_text()
             });
             break;
         }
@@ -677,7 +664,7 @@ ctx.addEventListener("message", (e) => {
                     engine.push_history();
                     from = engine.best_move().from;
                     to = engine.best_move().to;
-                    engine.move_list.push(engine.generate_move_string(from, to));
+                    engine.move_list.push(engine.last_move_san());
                 }
             } else {
                 engine.set_depth_searched_last_turn(-1);
@@ -702,7 +689,9 @@ ctx.addEventListener("message", (e) => {
                 inCheck: engine.in_check(),
                 captured: engine.piece_captured_this_turn(),
                 castled: engine.castled_this_turn(),
-                draw: engine.check_for_draw()
+                draw: engine.check_for_draw(),
+                checkmate: engine.is_checkmate(),
+                status: engine.game_status_text()
             });
             break;
         }
@@ -31,6 +31,8 @@ interface State {
     playAgainstBot: boolean;
     botIterative: boolean;
     currentEval: number;
+    currentMate: string;
+    currentPv: string;
     localHistory: History[];
     historyIndex: number;
     botMaxMoveTime: number;
@@ -38,6 +40,7 @@ interface State {
     statusDialogText: string;
     currentEngine: number;
     makeBookMoves: boolean;
+    promotionPiece: Piece;
 }
 
 interface History {
@@ -90,6 +93,8 @@ class _Board extends React.Component<Props, State> {
             playAgainstBot: false,
             botIterative: true,
             currentEval: 0,
+            currentMate: "",
+            currentPv: "",
             localHistory: [],
             historyIndex: 0,
             botMaxMoveTime: 3,
@@ -97,6 +102,7 @@ class _Board extends React.Component<Props, State> {
             statusDialogText: "",
             currentEngine: isIOS ? 0 : 1,
             makeBookMoves: true,
+            promotionPiece: Piece.Queen_W,
         };
 
         this.wasmWorker.onmessage = this.handleMessage;
@@ -182,6 +188,24 @@ class _Board extends React.Component<Props, State> {
                             label={<Typography color="textSecondary">Bot Iterative Deepening</Typography>}
                         />
                     </div>
+                    <div style={{ display: "flex", alignItems: "center" }}>
+                        <InfoButton title="Promotion Piece" dark>
+                            The piece your pawns promote to when they reach the last rank. Only the WebAssembly engine supports under-promotion, the Native JS engine always promotes to a queen.
+                        </InfoButton>
+                        <Typography color="textSecondary">Promote to:</Typography>
+                        <FormControl>
+                            <Select
+                                value={this.state.promotionPiece}
+                                onChange={(e) => this.setState({ promotionPiece: e.target.value as Piece })}
+                                style={{ color: theme.PALETTE_LIGHT_BLACK, borderColor: theme.PALETTE_BLACK, marginLeft: "0.5rem" }}
+                            >
+                                <MenuItem style={{ color: theme.PALETTE_LIGHT_BLACK }} value={Piece.Queen_W}>Queen</MenuItem>
+                                <MenuItem style={{ color: theme.PALETTE_LIGHT_BLACK }} value={Piece.Rook_W}>Rook</MenuItem>
+                                <MenuItem style={{ color: theme.PALETTE_LIGHT_BLACK }} value={Piece.Bishop_W}>Bishop</MenuItem>
+                                <MenuItem style={{ color: theme.PALETTE_LIGHT_BLACK }} value={Piece.Knight_W}>Knight</MenuItem>
+                            </Select>
+                        </FormControl>
+                    </div>
                     <div style={{ display: "flex", alignItems: "center" }}>
                         <InfoButton title="AI Engine" dark>
                             Choose which engine to run the AI with. To learn more about the individual engines, read the 'about' section of the site. WebAssembly is the recommended engine to use, and the engine can be changed at any point during a game.
@@ -293,7 +317,7 @@ class _Board extends React.Component<Props, State> {
     handleEvalMessage = (e: MessageEvent) => {
         switch (e.data.command) {
             case EvalCommands.ReceiveCurrentEval:
-                this.setState({ currentEval: e.data.eval });
+                this.setState({ currentEval: e.data.eval, currentMate: e.data.mate ?? "", currentPv: e.data.pv ?? "" });
                 break;
             default:
                 break;
@@ -339,7 +363,7 @@ class _Board extends React.Component<Props, State> {
                     if (!e.data.draw)
                         validMoves = e.data.validMoves;
 
-                    const checkmate = e.data.validMoves.length == 0;
+                    const checkmate = e.data.checkmate;
 
                     if (!checkmate && !e.data.draw && this.state.playAgainstBot)
                         this.botMove();
@@ -351,7 +375,7 @@ class _Board extends React.Component<Props, State> {
                         status = "Checkmate";
                     } else if (e.data.draw) {
                         soundToPlay = Sounds.GameOver;
-                        status = "Draw";
+                        status = "Draw: " + e.data.status;
                     } else {
                         if (e.data.inCheck)
                             soundToPlay = Sounds.Checked;
@@ -422,7 +446,7 @@ class _Board extends React.Component<Props, State> {
                     if (!e.data.draw)
                         validMoves = e.data.validMoves;
 
-                    const checkmate = e.data.validMoves.length == 0;
+                    const checkmate = e.data.checkmate;
     
                     if (!checkmate && !e.data.draw && this.state.botMoveAutoplay) {
                         this.engine().postMessage({ command: e.data.command, bookMoves: this.state.makeBookMoves });
@@ -437,7 +461,7 @@ class _Board extends React.Component<Props, State> {
                         status = "Checkmate";
                     } else if (e.data.draw) {
                         soundToPlay = Sounds.GameOver;
-                        status = "Draw"
+                        status = "Draw: " + e.data.status;
                     } else {
                         if (e.data.inCheck)
                             soundToPlay = Sounds.Checked;
@@ -709,12 +733,22 @@ class _Board extends React.Component<Props, State> {
         this.onMouseDown();
     }
 
+    // the piece a pawn dropped on the back rank promotes to, in the pawn's color
+    promotionFor = (toIndex: number) => {
+        const piece = this.localBoard[this.draggingIndex];
+        if (piece == Piece.Pawn_W && toIndex < 8)
+            return this.state.promotionPiece;
+        if (piece == Piece.Pawn_B && toIndex >= 56)
+            return this.state.promotionPiece - 6;
+        return Piece.Empty;
+    }
+
     onMouseUp = () => {
         if (this.draggingIndex != -1) {
             if (!this.state.waitingForMove) {
                 const boardIndex = this.getMouseBoardIndex();
                 if (boardIndex != this.draggingIndex)
-                    this.engine().postMessage({ command: EngineCommands.AttemptMove, fromIndex: this.draggingIndex, toIndex: boardIndex });
+                    this.engine().postMessage({ command: EngineCommands.AttemptMove, fromIndex: this.draggingIndex, toIndex: boardIndex, promotion: this.promotionFor(boardIndex) });
                 else
                     this.draggingIndex = -1;
             } else {
@@ -877,7 +911,7 @@ class _Board extends React.Component<Props, State> {
                     height={this.state.cellSize * 8}
                     style={{ touchAction: "none" }}
                 />
-                <EvaluationBar evaluation={this.state.currentEval} width={this.state.width} height={this.state.height} />
+                <EvaluationBar evaluation={this.state.currentEval} mate={this.state.currentMate} pv={this.state.currentPv} width={this.state.width} height={this.state.height} />
                 <br />
                 <br />
             </div>
@@ -4,6 +4,8 @@ import * as ThemeConstants from '../theme';
 
 interface Props {
     evaluation: number;
+    mate: string; // e.g. M3 or -M3, empty when there is no forced mate
+    pv: string; // the expected line, e.g. "1. e4 e5 2. Nf3"
     width: number;
     height: number;
 }
@@ -32,7 +34,8 @@ export const EvaluationBar = (props: Props) => {
     return (
         <div style={{ marginTop: "10px", display: "flex", flexDirection: "column", alignItems: "center" }}>
             <StyledLinearProgress variant="determinate" style={{ width: "100%", height: props.width < 900 ? "20px" : "40px" }} value={normalizeEval()} />
-            <Typography variant="h5" color="textPrimary">{`${props.evaluation > 0 ? '+' : ''}${Math.floor(Math.min(props.evaluation, 99999))}`}</Typography>
+            <Typography variant="h5" color="textPrimary">{props.mate != "" ? props.mate : `${props.evaluation > 0 ? '+' : ''}${Math.floor(Math.min(props.evaluation, 99999))}`}</Typography>
+            <Typography variant="body2" color="textSecondary">{props.pv}</Typography>
         </div>
     );
 }
\ No newline at end of file